@@ -0,0 +1,166 @@
+use crate::{Plugin, PluginDomain, PluginError};
+use std::fmt::{Debug, Formatter};
+use xaoc_utils::label::{Label, LabelSet};
+
+/// Takes the app when it is [run][App::run], e.g. to drive it in a loop until the program exits.
+pub type AppRunner = Box<dyn FnOnce(App)>;
+
+/// A program made of [`Plugin`]s, one of which typically sets the runner that drives it.
+///
+/// ```
+/// use xaoc_app::prelude::*;
+///
+/// struct Headless;
+///
+/// impl Plugin for Headless {
+///     fn build(&self, app: &mut App) {
+///         app.set_runner(|app| assert_eq!(app.plugins().len(), 1));
+///     }
+/// }
+///
+/// App::new().add_plugin(Headless).run();
+/// ```
+pub struct App {
+    plugins: Vec<Label<PluginDomain>>,
+    unique_plugins: LabelSet<PluginDomain>,
+    runner: AppRunner,
+}
+
+impl App {
+    /// Creates an app without plugins, whose runner does nothing until one is [set][App::set_runner].
+    pub fn new() -> Self {
+        Self { plugins: Vec::new(), unique_plugins: LabelSet::new(), runner: Box::new(|_| {}) }
+    }
+
+    /// Builds the plugin into the app, panicking if it is unique and a plugin with its label was already added,
+    /// or if one of its [dependencies][Plugin::dependencies] was not added before.
+    pub fn add_plugin<P: Plugin>(&mut self, plugin: P) -> &mut Self {
+        if let Err(error) = self.try_add_plugin(plugin) {
+            panic!("{error}");
+        }
+        self
+    }
+
+    /// Like [`App::add_plugin`], but returns an error instead of panicking.
+    pub fn try_add_plugin<P: Plugin>(&mut self, plugin: P) -> Result<&mut Self, PluginError> {
+        self.add_boxed_plugin(Box::new(plugin))
+    }
+
+    pub(crate) fn add_boxed_plugin(&mut self, plugin: Box<dyn Plugin>) -> Result<&mut Self, PluginError> {
+        let label = plugin.label();
+        if let Some(dependency) = plugin.dependencies().iter().find(|dependency| !self.has_plugin(dependency.label())) {
+            return Err(PluginError::MissingDependency {
+                plugin: label,
+                dependency: dependency.name(),
+                disabled: false,
+            });
+        }
+        if plugin.is_unique() && !self.unique_plugins.insert(label) {
+            return Err(PluginError::Duplicate(label));
+        }
+        self.plugins.push(label);
+        plugin.build(self);
+        Ok(self)
+    }
+
+    /// Whether a plugin with the label was added.
+    pub fn has_plugin(&self, label: Label<PluginDomain>) -> bool {
+        self.plugins.contains(&label)
+    }
+
+    /// Whether a unique plugin with the label was added.
+    pub(crate) fn has_unique_plugin(&self, label: Label<PluginDomain>) -> bool {
+        self.unique_plugins.contains(label)
+    }
+
+    /// The labels of the added plugins, in the order they were added.
+    pub fn plugins(&self) -> &[Label<PluginDomain>] {
+        &self.plugins
+    }
+
+    /// Sets the function taking the app when it is [run][App::run].
+    pub fn set_runner<F: FnOnce(App) + 'static>(&mut self, runner: F) -> &mut Self {
+        self.runner = Box::new(runner);
+        self
+    }
+
+    /// Hands the app over to its runner, leaving an empty app in its place.
+    pub fn run(&mut self) {
+        let mut app = std::mem::take(self);
+        let runner = std::mem::replace(&mut app.runner, Box::new(|_| {}));
+        runner(app);
+    }
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for App {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("App")
+            .field("plugins", &self.plugins.iter().map(|label| label.name()).collect::<Vec<_>>())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    struct First;
+
+    impl Plugin for First {
+        fn build(&self, app: &mut App) {
+            app.add_plugin(Repeated("first"));
+        }
+    }
+
+    struct Repeated(&'static str);
+
+    impl Plugin for Repeated {
+        fn build(&self, _app: &mut App) {}
+
+        fn label(&self) -> Label<PluginDomain> {
+            Label::new(self.0)
+        }
+
+        fn is_unique(&self) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn plugins() {
+        let mut app = App::new();
+        app.add_plugin(First).add_plugin(Repeated("a")).add_plugin(Repeated("a"));
+        assert_eq!(app.try_add_plugin(First).unwrap_err(), PluginError::Duplicate(First.label()));
+        assert!(app.has_plugin(First.label()));
+        let names: Vec<_> = app.plugins().iter().map(|label| label.name()).collect();
+        assert_eq!(names, [First.label().name(), "first", "a", "a"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "was already added")]
+    fn duplicate_plugin() {
+        App::new().add_plugin(First).add_plugin(First);
+    }
+
+    #[test]
+    fn runner() {
+        let ran = Rc::new(Cell::new(false));
+        let mut app = App::new();
+        let runner_ran = ran.clone();
+        app.add_plugin(First).set_runner(move |app| {
+            assert!(app.has_plugin(First.label()));
+            runner_ran.set(true);
+        });
+        app.run();
+        assert!(ran.get());
+        assert!(app.plugins().is_empty());
+    }
+}
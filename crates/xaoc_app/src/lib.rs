@@ -1,8 +1,11 @@
-#[cfg(test)]
-mod tests {
-    #[test]
-    fn it_works() {
-        let result = 2 + 2;
-        assert_eq!(result, 4);
-    }
+mod app;
+mod plugin;
+mod plugin_group;
+
+pub use app::{App, AppRunner};
+pub use plugin::{Plugin, PluginDomain, PluginError};
+pub use plugin_group::{PluginGroup, PluginGroupBuilder};
+
+pub mod prelude {
+    pub use crate::{App, Plugin, PluginGroup};
 }
@@ -0,0 +1,88 @@
+use crate::App;
+use std::fmt::{Display, Formatter};
+use xaoc_utils::label::{ConstLabel, Label, LabelDomain};
+
+/// The label domain of plugins, see [`Plugin::label`].
+pub struct PluginDomain;
+
+impl LabelDomain for PluginDomain {
+    const NAME: &'static str = "xaoc_app::plugins";
+}
+
+/// A part of an [`App`], which configures the app when it is added to it.
+///
+/// ```
+/// use xaoc_app::prelude::*;
+///
+/// struct RenderPlugin;
+///
+/// impl Plugin for RenderPlugin {
+///     fn build(&self, _app: &mut App) {}
+/// }
+///
+/// struct GamePlugin;
+///
+/// impl Plugin for GamePlugin {
+///     fn build(&self, app: &mut App) {
+///         app.add_plugin(RenderPlugin);
+///     }
+/// }
+///
+/// let mut app = App::new();
+/// app.add_plugin(GamePlugin);
+/// assert!(app.has_plugin(RenderPlugin.label()));
+/// ```
+pub trait Plugin: 'static {
+    /// Configures the app.
+    fn build(&self, app: &mut App);
+
+    /// Identifies the plugin, the type name by default. A unique plugin can only be added once per label.
+    fn label(&self) -> Label<PluginDomain> {
+        Label::new(std::any::type_name::<Self>())
+    }
+
+    /// Whether the plugin can only be added once, `true` by default.
+    fn is_unique(&self) -> bool {
+        true
+    }
+
+    /// The labels of the plugins which must be built before this one, none by default. A [`PluginGroup`]
+    /// orders its plugins after their dependencies.
+    ///
+    /// [`PluginGroup`]: crate::PluginGroup
+    fn dependencies(&self) -> &[ConstLabel<PluginDomain>] {
+        &[]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PluginError {
+    /// A unique plugin with this label was already added.
+    Duplicate(Label<PluginDomain>),
+    /// The plugin depends on a plugin which was neither added before nor is in its group, or is disabled in it.
+    MissingDependency { plugin: Label<PluginDomain>, dependency: &'static str, disabled: bool },
+    /// The plugins of a group depend on each other in this cycle, each on the next, the first repeated last.
+    Cycle(Vec<Label<PluginDomain>>),
+}
+
+impl Display for PluginError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Duplicate(label) => write!(f, "plugin {} was already added", label.name()),
+            Self::MissingDependency { plugin, dependency, disabled } => {
+                let reason = if *disabled { "is disabled" } else { "was not added" };
+                write!(f, "plugin {} depends on {dependency}, which {reason}", plugin.name())
+            }
+            Self::Cycle(labels) => {
+                f.write_str("plugins depend on each other: ")?;
+                for (index, label) in labels.iter().enumerate() {
+                    let separator = if index == 0 { "" } else { " -> " };
+                    write!(f, "{separator}{}", label.name())?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for PluginError {}
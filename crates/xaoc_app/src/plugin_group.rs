@@ -0,0 +1,355 @@
+use crate::{App, Plugin, PluginDomain, PluginError};
+use std::collections::BTreeSet;
+use xaoc_utils::label::{Label, LabelId, LabelSet};
+
+/// A set of plugins added together with [`App::add_plugins`], after sorting them by their
+/// [dependencies][Plugin::dependencies].
+///
+/// ```
+/// use xaoc_app::prelude::*;
+/// use xaoc_app::{PluginDomain, PluginGroupBuilder};
+/// use xaoc_utils::label::{ConstLabel, Label};
+///
+/// struct AudioPlugin;
+///
+/// impl AudioPlugin {
+///     const LABEL: ConstLabel<PluginDomain> = ConstLabel::new("audio");
+/// }
+///
+/// impl Plugin for AudioPlugin {
+///     fn build(&self, _app: &mut App) {}
+///
+///     fn label(&self) -> Label<PluginDomain> {
+///         Self::LABEL.label()
+///     }
+/// }
+///
+/// struct RenderPlugin;
+///
+/// impl Plugin for RenderPlugin {
+///     fn build(&self, _app: &mut App) {}
+/// }
+///
+/// struct DefaultPlugins;
+///
+/// impl PluginGroup for DefaultPlugins {
+///     fn build(self) -> PluginGroupBuilder {
+///         PluginGroupBuilder::new().add_plugin(AudioPlugin).add_plugin(RenderPlugin)
+///     }
+/// }
+///
+/// let mut server = App::new();
+/// server.add_plugins(DefaultPlugins.build().disable(AudioPlugin::LABEL));
+/// assert!(!server.has_plugin(AudioPlugin.label()));
+/// assert!(server.has_plugin(RenderPlugin.label()));
+/// ```
+pub trait PluginGroup {
+    fn build(self) -> PluginGroupBuilder;
+}
+
+struct Entry {
+    label: Label<PluginDomain>,
+    plugin: Box<dyn Plugin>,
+    enabled: bool,
+}
+
+/// The plugins of a [`PluginGroup`], in the order they were added, which can be disabled or replaced
+/// before the group is added to an app.
+#[derive(Default)]
+pub struct PluginGroupBuilder {
+    plugins: Vec<Entry>,
+}
+
+impl PluginGroupBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the plugin. A [unique][Plugin::is_unique] plugin replaces the unique plugin with the same label
+    /// in its place if any, other plugins are added after the plugins added before.
+    pub fn add_plugin<P: Plugin>(mut self, plugin: P) -> Self {
+        let label = plugin.label();
+        let unique = plugin.is_unique();
+        let entry = Entry { label, plugin: Box::new(plugin), enabled: true };
+        match self.plugins.iter_mut().find(|other| unique && other.label == label && other.plugin.is_unique()) {
+            Some(other) => *other = entry,
+            None => self.plugins.push(entry),
+        }
+        self
+    }
+
+    /// Replaces the plugins with the same label by this one, in the place of the first of them and keeping
+    /// whether it is enabled. Panics if the group has no plugin with its label.
+    pub fn set_plugin<P: Plugin>(mut self, plugin: P) -> Self {
+        let label = plugin.label();
+        let Some(first) = self.plugins.iter().position(|entry| entry.label == label) else {
+            panic!("plugin group has no plugin {} to replace", label.name());
+        };
+        self.plugins[first].plugin = Box::new(plugin);
+        let mut index = 0;
+        self.plugins.retain(|entry| {
+            let keep = index == first || entry.label != label;
+            index += 1;
+            keep
+        });
+        self
+    }
+
+    /// Disables the plugins with the label, so that they are not added with the group.
+    /// Panics if the group has no plugin with the label.
+    pub fn disable(self, label: impl Into<Label<PluginDomain>>) -> Self {
+        self.enabled(label.into(), false)
+    }
+
+    /// Enables the plugins with the label again. Panics if the group has no plugin with the label.
+    pub fn enable(self, label: impl Into<Label<PluginDomain>>) -> Self {
+        self.enabled(label.into(), true)
+    }
+
+    fn enabled(mut self, label: Label<PluginDomain>, enabled: bool) -> Self {
+        let mut found = false;
+        for entry in self.plugins.iter_mut().filter(|entry| entry.label == label) {
+            entry.enabled = enabled;
+            found = true;
+        }
+        assert!(found, "plugin group has no plugin {}", label.name());
+        self
+    }
+
+    /// The labels of the plugins, with whether they are enabled, in the order they were added.
+    pub fn plugins(&self) -> impl Iterator<Item = (Label<PluginDomain>, bool)> + '_ {
+        self.plugins.iter().map(|entry| (entry.label, entry.enabled))
+    }
+
+    /// Orders the enabled plugins after their dependencies, and otherwise in the order they were added,
+    /// checking that they can all be added to the `app`. Dependencies may also be plugins already added to it.
+    fn sort(self, app: &App) -> Result<Vec<Box<dyn Plugin>>, PluginError> {
+        let disabled: Vec<LabelId> =
+            self.plugins.iter().filter(|entry| !entry.enabled).map(|entry| entry.label.id()).collect();
+        let plugins: Vec<(Label<PluginDomain>, Box<dyn Plugin>)> =
+            self.plugins.into_iter().filter(|entry| entry.enabled).map(|entry| (entry.label, entry.plugin)).collect();
+
+        let mut unique = LabelSet::new();
+        for (label, plugin) in &plugins {
+            if plugin.is_unique() && (app.has_unique_plugin(*label) || !unique.insert(*label)) {
+                return Err(PluginError::Duplicate(*label));
+            }
+        }
+
+        // The plugins each plugin depends on, and the plugins depending on it, by index.
+        let mut dependencies = vec![Vec::new(); plugins.len()];
+        let mut dependents = vec![Vec::new(); plugins.len()];
+        for (index, (label, plugin)) in plugins.iter().enumerate() {
+            for dependency in plugin.dependencies() {
+                let mut found = false;
+                for (other, _) in plugins.iter().enumerate().filter(|(_, (label, _))| label.id() == dependency.id()) {
+                    dependencies[index].push(other);
+                    dependents[other].push(index);
+                    found = true;
+                }
+                match found {
+                    true => {}
+                    false if app.has_plugin(dependency.label()) => {}
+                    false => {
+                        return Err(PluginError::MissingDependency {
+                            plugin: *label,
+                            dependency: dependency.name(),
+                            disabled: disabled.contains(&dependency.id()),
+                        })
+                    }
+                }
+            }
+        }
+
+        let mut waiting: Vec<usize> = dependencies.iter().map(Vec::len).collect();
+        let mut ready: BTreeSet<usize> = (0..plugins.len()).filter(|&index| waiting[index] == 0).collect();
+        let mut order = Vec::with_capacity(plugins.len());
+        while let Some(index) = ready.pop_first() {
+            order.push(index);
+            for &dependent in &dependents[index] {
+                waiting[dependent] -= 1;
+                if waiting[dependent] == 0 {
+                    ready.insert(dependent);
+                }
+            }
+        }
+        if order.len() < plugins.len() {
+            return Err(PluginError::Cycle(
+                cycle(&dependencies, &waiting).into_iter().map(|index| plugins[index].0).collect(),
+            ));
+        }
+
+        let mut plugins: Vec<_> = plugins.into_iter().map(|(_, plugin)| Some(plugin)).collect();
+        Ok(order.into_iter().map(|index| plugins[index].take().unwrap()).collect())
+    }
+}
+
+/// Finds a cycle among the plugins still waiting for dependencies, each followed by one it depends on.
+fn cycle(dependencies: &[Vec<usize>], waiting: &[usize]) -> Vec<usize> {
+    let mut path = vec![waiting.iter().position(|&count| count > 0).unwrap()];
+    loop {
+        let last = *path.last().unwrap();
+        let next = *dependencies[last].iter().find(|&&dependency| waiting[dependency] > 0).unwrap();
+        if let Some(start) = path.iter().position(|&index| index == next) {
+            path.drain(..start);
+            path.push(next);
+            return path;
+        }
+        path.push(next);
+    }
+}
+
+impl PluginGroup for PluginGroupBuilder {
+    fn build(self) -> PluginGroupBuilder {
+        self
+    }
+}
+
+impl App {
+    /// Adds the enabled plugins of the group after their dependencies, panicking on the errors
+    /// of [`App::try_add_plugins`].
+    pub fn add_plugins<G: PluginGroup>(&mut self, group: G) -> &mut Self {
+        if let Err(error) = self.try_add_plugins(group) {
+            panic!("{error}");
+        }
+        self
+    }
+
+    /// Like [`App::add_plugins`], but returns an error instead of panicking if a dependency is neither in
+    /// the group nor added before, if the dependencies form a cycle, or if a unique plugin was added before.
+    /// No plugin of the group is added on error, unless the error comes from plugins added by the plugins of
+    /// the group while they are built.
+    pub fn try_add_plugins<G: PluginGroup>(&mut self, group: G) -> Result<&mut Self, PluginError> {
+        for plugin in group.build().sort(self)? {
+            self.add_boxed_plugin(plugin)?;
+        }
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xaoc_utils::label::ConstLabel;
+
+    struct Named {
+        label: ConstLabel<PluginDomain>,
+        dependencies: Vec<ConstLabel<PluginDomain>>,
+    }
+
+    fn named(name: &'static str, dependencies: &[&'static str]) -> Named {
+        Named {
+            label: ConstLabel::new(name),
+            dependencies: dependencies.iter().map(|&name| ConstLabel::new(name)).collect(),
+        }
+    }
+
+    impl Plugin for Named {
+        fn build(&self, _app: &mut App) {}
+
+        fn label(&self) -> Label<PluginDomain> {
+            self.label.label()
+        }
+
+        fn dependencies(&self) -> &[ConstLabel<PluginDomain>] {
+            &self.dependencies
+        }
+    }
+
+    fn names(app: &App) -> Vec<&str> {
+        app.plugins().iter().map(|label| label.name()).collect()
+    }
+
+    #[test]
+    fn dependency_order() {
+        let group = PluginGroupBuilder::new()
+            .add_plugin(named("render", &["window", "assets"]))
+            .add_plugin(named("window", &[]))
+            .add_plugin(named("ui", &["render"]))
+            .add_plugin(named("assets", &["core"]));
+        let mut app = App::new();
+        app.add_plugin(named("core", &[])).add_plugins(group);
+        assert_eq!(names(&app), ["core", "window", "assets", "render", "ui"]);
+    }
+
+    #[test]
+    fn disable_and_replace() {
+        let group = || PluginGroupBuilder::new().add_plugin(named("audio", &[])).add_plugin(named("render", &[]));
+        let mut app = App::new();
+        app.add_plugins(
+            group()
+                .disable(ConstLabel::new("audio"))
+                .set_plugin(named("render", &["core"]))
+                .add_plugin(named("core", &[])),
+        );
+        assert_eq!(names(&app), ["core", "render"]);
+
+        let group = group().disable(ConstLabel::new("audio")).add_plugin(named("audio_ui", &["audio"]));
+        assert_eq!(
+            App::new().try_add_plugins(group).unwrap_err().to_string(),
+            "plugin audio_ui depends on audio, which is disabled"
+        );
+        let error = App::new().try_add_plugins(PluginGroupBuilder::new().add_plugin(named("a", &["b"]))).unwrap_err();
+        assert_eq!(error.to_string(), "plugin a depends on b, which was not added");
+    }
+
+    struct Repeated(&'static str);
+
+    impl Plugin for Repeated {
+        fn build(&self, _app: &mut App) {}
+
+        fn label(&self) -> Label<PluginDomain> {
+            Label::new(self.0)
+        }
+
+        fn is_unique(&self) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn repeated() {
+        let group = PluginGroupBuilder::new()
+            .add_plugin(Repeated("layer"))
+            .add_plugin(named("ui", &["layer"]))
+            .add_plugin(Repeated("layer"))
+            .add_plugin(named("ui", &[]));
+        assert_eq!(
+            group.plugins().map(|(label, _)| label.name().to_string()).collect::<Vec<_>>(),
+            ["layer", "ui", "layer"]
+        );
+        let mut app = App::new();
+        app.add_plugins(group.set_plugin(named("ui", &["layer"])));
+        assert_eq!(names(&app), ["layer", "layer", "ui"]);
+
+        let group = PluginGroupBuilder::new().add_plugin(Repeated("layer")).add_plugin(Repeated("layer"));
+        let group = group.set_plugin(Repeated("layer"));
+        assert_eq!(group.plugins().count(), 1);
+        assert_eq!(
+            group.disable(ConstLabel::new("layer")).plugins().collect::<Vec<_>>(),
+            [(Label::new("layer"), false)]
+        );
+    }
+
+    #[test]
+    fn duplicates() {
+        let mut app = App::new();
+        app.add_plugin(named("core", &[]));
+        let group = PluginGroupBuilder::new().add_plugin(named("window", &[])).add_plugin(named("core", &[]));
+        assert_eq!(app.try_add_plugins(group).unwrap_err(), PluginError::Duplicate(Label::new("core")));
+        assert_eq!(names(&app), ["core"]);
+    }
+
+    #[test]
+    fn cycles() {
+        let group = PluginGroupBuilder::new()
+            .add_plugin(named("x", &[]))
+            .add_plugin(named("a", &["c"]))
+            .add_plugin(named("b", &["a", "x"]))
+            .add_plugin(named("c", &["b"]));
+        let mut app = App::new();
+        let error = app.try_add_plugins(group).unwrap_err();
+        assert_eq!(error.to_string(), "plugins depend on each other: a -> c -> b -> a");
+        assert!(app.plugins().is_empty());
+    }
+}
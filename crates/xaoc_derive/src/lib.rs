@@ -0,0 +1,206 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, parse_quote, Data, DeriveInput, Error, Expr, Fields, GenericParam, LitStr, Meta, WherePredicate,
+};
+
+/// Derives [`Default`] for a struct, with the default of each field given by its `default` attribute
+/// or by the [`Default`] of its type:
+/// - `#[default = literal]` uses the literal, as the compiler allows nothing else after `=`,
+/// - `#[default(expr)]` evaluates any other expression, such as `-1` or `Vec::with_capacity(4)`,
+/// - `#[default(fn_path)]` calls `fn_path()` when the expression is a bare path, wrap a constant in a block
+///   to use its value instead: `#[default({ MAX_SIZE })]`.
+///
+/// ```
+/// use xaoc_utils::prelude::*;
+///
+/// fn default_title() -> String {
+///     "xaoc".into()
+/// }
+///
+/// #[derive(XaocDefault)]
+/// struct WindowConfig {
+///     #[default = 1280]
+///     width: u32,
+///     #[default = 720]
+///     height: u32,
+///     #[default(default_title)]
+///     title: String,
+///     #[default(-1)]
+///     monitor: i32,
+///     fullscreen: bool,
+/// }
+///
+/// let config = WindowConfig { fullscreen: true, ..default() };
+/// assert_eq!((config.width, config.height, config.title.as_str()), (1280, 720, "xaoc"));
+/// ```
+///
+/// Only structs are supported, enums can derive [`Default`] with a `#[default]` variant:
+/// ```compile_fail
+/// use xaoc_utils::prelude::*;
+///
+/// #[derive(XaocDefault)]
+/// enum Mode {
+///     Windowed,
+///     Fullscreen,
+/// }
+/// ```
+#[proc_macro_derive(XaocDefault, attributes(default))]
+pub fn derive_xaoc_default(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    xaoc_default(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn xaoc_default(mut input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(&input.ident, "XaocDefault can only be derived for structs"));
+    };
+
+    let generic = !input.generics.params.is_empty();
+    let mut bounds: Vec<WherePredicate> = Vec::new();
+    let mut values = Vec::new();
+    for field in data.fields.iter() {
+        let value = match field_default(&field.attrs)? {
+            Some(value) => value,
+            None => {
+                if generic {
+                    let ty = &field.ty;
+                    bounds.push(parse_quote!(#ty: ::core::default::Default));
+                }
+                quote!(::core::default::Default::default())
+            }
+        };
+        values.push(match &field.ident {
+            Some(ident) => quote!(#ident: #value),
+            None => value,
+        });
+    }
+    let body = match &data.fields {
+        Fields::Named(_) => quote!(Self { #(#values),* }),
+        Fields::Unnamed(_) => quote!(Self(#(#values),*)),
+        Fields::Unit => quote!(Self),
+    };
+
+    input.generics.make_where_clause().predicates.extend(bounds);
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::core::default::Default for #ident #ty_generics #where_clause {
+            fn default() -> Self {
+                #body
+            }
+        }
+    })
+}
+
+/// The default value given by the `default` attribute of a field, if any.
+fn field_default(attrs: &[syn::Attribute]) -> syn::Result<Option<TokenStream2>> {
+    let mut value = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("default")) {
+        if value.is_some() {
+            return Err(Error::new_spanned(attr, "duplicate default attribute"));
+        }
+        value = Some(match &attr.meta {
+            Meta::NameValue(meta) => {
+                let expr: &Expr = &meta.value;
+                quote!(#expr)
+            }
+            Meta::List(meta) => match meta.parse_args()? {
+                Expr::Path(path) => quote!(#path()),
+                expr => quote!(#expr),
+            },
+            Meta::Path(_) => {
+                return Err(Error::new_spanned(attr, "expected `#[default = literal]` or `#[default(expr)]`"));
+            }
+        });
+    }
+    Ok(value)
+}
+
+/// Derives `StableTypeId` from the fully qualified path of the type, or from the UUID declared with
+/// `#[stable_type_id(uuid = "...")]`, which keeps the id when the type is moved or renamed.
+/// The ids of generic types are combined with the ids of their type parameters.
+///
+/// ```
+/// use xaoc_utils::hash::{combine_type_ids, parse_uuid, StableTypeId};
+///
+/// #[derive(StableTypeId)]
+/// struct Player;
+///
+/// #[derive(StableTypeId)]
+/// #[stable_type_id(uuid = "67e55044-10b1-426f-9247-bb680e5fe0c8")]
+/// struct Handle<T>(T);
+///
+/// let handle = parse_uuid("67e55044-10b1-426f-9247-bb680e5fe0c8");
+/// assert_eq!(Handle::<Player>::STABLE_TYPE_ID, combine_type_ids(handle, &[Player::STABLE_TYPE_ID]));
+/// ```
+///
+/// Types with lifetime parameters can't have a stable id, as it requires them to be `'static`:
+/// ```compile_fail
+/// use xaoc_utils::hash::StableTypeId;
+///
+/// #[derive(StableTypeId)]
+/// struct Name<'a>(&'a str);
+/// ```
+#[proc_macro_derive(StableTypeId, attributes(stable_type_id))]
+pub fn derive_stable_type_id(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    stable_type_id(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn stable_type_id(mut input: DeriveInput) -> syn::Result<TokenStream2> {
+    let ident = &input.ident;
+    let base = match declared_uuid(&input.attrs)? {
+        Some(uuid) => quote!(::xaoc_utils::hash::parse_uuid(#uuid)),
+        None => quote!(::xaoc_utils::hash::type_path_id(::core::concat!(
+            ::core::module_path!(),
+            "::",
+            ::core::stringify!(#ident)
+        ))),
+    };
+
+    let mut parameters = Vec::new();
+    for param in &input.generics.params {
+        match param {
+            GenericParam::Type(param) => parameters.push(param.ident.clone()),
+            GenericParam::Lifetime(param) => {
+                return Err(Error::new_spanned(param, "StableTypeId can't be derived for types with lifetimes"));
+            }
+            GenericParam::Const(param) => {
+                return Err(Error::new_spanned(param, "StableTypeId can't be derived for types with const parameters"));
+            }
+        }
+    }
+    let bounds: Vec<WherePredicate> =
+        parameters.iter().map(|param| parse_quote!(#param: ::xaoc_utils::hash::StableTypeId)).collect();
+    input.generics.make_where_clause().predicates.extend(bounds);
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::xaoc_utils::hash::StableTypeId for #ident #ty_generics #where_clause {
+            const STABLE_TYPE_ID: u128 = ::xaoc_utils::hash::combine_type_ids(
+                #base,
+                &[#(<#parameters as ::xaoc_utils::hash::StableTypeId>::STABLE_TYPE_ID),*],
+            );
+        }
+    })
+}
+
+/// The UUID given by the `stable_type_id` attribute of a type, if any.
+fn declared_uuid(attrs: &[syn::Attribute]) -> syn::Result<Option<LitStr>> {
+    let mut uuid = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("stable_type_id")) {
+        attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident("uuid") {
+                return Err(meta.error("expected `#[stable_type_id(uuid = \"...\")]`"));
+            }
+            if uuid.is_some() {
+                return Err(meta.error("duplicate uuid"));
+            }
+            uuid = Some(meta.value()?.parse()?);
+            Ok(())
+        })?;
+    }
+    Ok(uuid)
+}
@@ -1,2 +1,4 @@
+#![allow(clippy::single_component_path_imports)]
+
 #[allow(unused_imports)]
 use xaoc_lib;
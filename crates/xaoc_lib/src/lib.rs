@@ -1 +1,7 @@
+pub use xaoc_app as app;
+pub use xaoc_utils as utils;
 
+pub mod prelude {
+    pub use xaoc_app::prelude::*;
+    pub use xaoc_utils::prelude::*;
+}
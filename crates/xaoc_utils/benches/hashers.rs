@@ -0,0 +1,120 @@
+//! Compares the hashers and map aliases of `xaoc_utils::hash` across key shapes.
+//!
+//! Run with `cargo bench -p xaoc_utils --bench hashers`.
+
+use ahash::RandomState;
+use criterion::measurement::WallTime;
+use criterion::{criterion_group, criterion_main, BenchmarkGroup, BenchmarkId, Criterion, Throughput};
+use std::any::TypeId;
+use std::hash::{BuildHasher, Hash};
+use std::hint::black_box;
+use xaoc_utils::hash::{FixedState, Hashed, IntHash, PassHash, StableState};
+use xaoc_utils::label::{ConstLabel, LabelDomain, LabelId};
+
+const KEYS: u32 = 10_000;
+
+struct Bench;
+
+impl LabelDomain for Bench {
+    const NAME: &'static str = "xaoc_utils::benches::hashers::Bench";
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct Large([u64; 32]);
+
+/// Benchmarks filling a map with the keys, and then looking every key up.
+fn map<K: Hash + Eq + Clone, S: BuildHasher + Default>(group: &mut BenchmarkGroup<WallTime>, name: &str, keys: &[K]) {
+    let map: hashbrown::HashMap<K, usize, S> = keys.iter().cloned().zip(0..).collect();
+    group.bench_function(BenchmarkId::new("insert", name), |b| {
+        b.iter(|| keys.iter().cloned().zip(0..).collect::<hashbrown::HashMap<K, usize, S>>())
+    });
+    group.bench_function(BenchmarkId::new("get", name), |b| {
+        b.iter(|| keys.iter().map(|key| map[black_box(key)]).sum::<usize>())
+    });
+}
+
+fn hashed<K: Hash + Clone>(keys: &[K]) -> Vec<Hashed<K>> {
+    keys.iter().cloned().map(Hashed::new).collect()
+}
+
+fn sequential_u32(c: &mut Criterion) {
+    let mut group = c.benchmark_group("u32_sequential");
+    group.throughput(Throughput::Elements(KEYS as u64));
+    let keys: Vec<u32> = (0..KEYS).collect();
+    map::<_, RandomState>(&mut group, "RandomState", &keys);
+    map::<_, FixedState>(&mut group, "FixedState", &keys);
+    map::<_, IntHash>(&mut group, "IntHash", &keys);
+    map::<_, PassHash>(&mut group, "PassHash", &keys.iter().map(|&key| key as u64).collect::<Vec<_>>());
+    map::<_, PassHash>(&mut group, "PassHash over Hashed", &hashed(&keys));
+    group.finish();
+}
+
+fn type_ids(c: &mut Criterion) {
+    fn ids<T: 'static>() -> [TypeId; 8] {
+        [
+            TypeId::of::<T>(),
+            TypeId::of::<Option<T>>(),
+            TypeId::of::<Vec<T>>(),
+            TypeId::of::<Box<T>>(),
+            TypeId::of::<(T, u8)>(),
+            TypeId::of::<[T; 2]>(),
+            TypeId::of::<&'static T>(),
+            TypeId::of::<fn(T)>(),
+        ]
+    }
+    let keys: Vec<TypeId> = [ids::<u8>(), ids::<u16>(), ids::<u32>(), ids::<u64>(), ids::<String>(), ids::<Large>()]
+        .into_iter()
+        .flatten()
+        .collect();
+
+    let mut group = c.benchmark_group("type_id");
+    group.throughput(Throughput::Elements(keys.len() as u64));
+    map::<_, RandomState>(&mut group, "RandomState", &keys);
+    map::<_, FixedState>(&mut group, "FixedState", &keys);
+    map::<_, IntHash>(&mut group, "IntHash", &keys);
+    group.finish();
+}
+
+fn short_strings(c: &mut Criterion) {
+    let names: Vec<&'static str> = (0..KEYS).map(|i| &*Box::leak(format!("entity_{i}").into_boxed_str())).collect();
+    let keys: Vec<String> = names.iter().map(|name| name.to_string()).collect();
+    let ids: Vec<LabelId> = names.iter().map(|&name| ConstLabel::<Bench>::new(name).id()).collect();
+
+    let mut group = c.benchmark_group("short_strings");
+    group.throughput(Throughput::Elements(KEYS as u64));
+    map::<_, RandomState>(&mut group, "RandomState", &keys);
+    map::<_, FixedState>(&mut group, "FixedState", &keys);
+    map::<_, PassHash>(&mut group, "PassHash over Hashed", &hashed(&keys));
+    map::<_, PassHash>(&mut group, "PassHash over label ids", &ids);
+
+    group.bench_function(BenchmarkId::new("hash", "RandomState"), |b| {
+        let state = RandomState::new();
+        b.iter(|| names.iter().map(|name| state.hash_one(black_box(name))).fold(0, u64::wrapping_add))
+    });
+    group.bench_function(BenchmarkId::new("hash", "FixedState"), |b| {
+        b.iter(|| names.iter().map(|name| FixedState.hash_one(black_box(name))).fold(0, u64::wrapping_add))
+    });
+    group.bench_function(BenchmarkId::new("hash", "StableState"), |b| {
+        b.iter(|| names.iter().map(|name| StableState.hash_one(black_box(name))).fold(0, u64::wrapping_add))
+    });
+    group.bench_function(BenchmarkId::new("hash", "FNV-1a label id"), |b| {
+        b.iter(|| {
+            names.iter().map(|&name| ConstLabel::<Bench>::new(black_box(name)).id()).fold(0, LabelId::wrapping_add)
+        })
+    });
+    group.finish();
+}
+
+fn large_structs(c: &mut Criterion) {
+    let keys: Vec<Large> = (0..KEYS as u64 / 10).map(|i| Large([i; 32])).collect();
+
+    let mut group = c.benchmark_group("large_struct");
+    group.throughput(Throughput::Elements(keys.len() as u64));
+    map::<_, RandomState>(&mut group, "RandomState", &keys);
+    map::<_, FixedState>(&mut group, "FixedState", &keys);
+    map::<_, PassHash>(&mut group, "PassHash over Hashed", &hashed(&keys));
+    group.finish();
+}
+
+criterion_group!(benches, sequential_u32, type_ids, short_strings, large_structs);
+criterion_main!(benches);
@@ -0,0 +1,94 @@
+//! Prints how often `ConstLabel` ids collide over corpora of realistic label names, in full and when truncated,
+//! next to the number of collisions expected from ideally random ids.
+//!
+//! Run with `cargo bench -p xaoc_utils --bench label_collisions`.
+
+use xaoc_utils::label::{ConstLabel, LabelDomain, LabelId};
+
+struct Names;
+
+impl LabelDomain for Names {
+    const NAME: &'static str = "xaoc_utils::benches::label_collisions::Names";
+}
+
+/// Schedule labels, like `app/render/shadow_pass_3`.
+fn system_paths() -> Vec<String> {
+    let plugins = ["app", "render", "physics", "audio", "ui", "input", "net", "anim", "ai", "editor"];
+    let stages = ["first", "pre_update", "update", "post_update", "extract", "prepare", "queue", "last"];
+    let systems = ["spawn", "despawn", "sync", "apply", "collect", "sort", "flush", "upload", "cull", "resolve"];
+    let mut names = Vec::new();
+    for plugin in plugins {
+        for stage in stages {
+            for system in systems {
+                for i in 0..25 {
+                    names.push(format!("{plugin}/{stage}/{system}_{i}"));
+                }
+            }
+        }
+    }
+    names
+}
+
+/// Asset paths, like `textures/props/crate_03_albedo.png`.
+fn asset_paths() -> Vec<String> {
+    let folders = ["textures", "meshes", "materials", "sounds", "shaders", "scenes", "fonts", "animations"];
+    let groups = ["props", "characters", "terrain", "vfx", "ui", "vehicles", "buildings", "foliage"];
+    let suffixes = ["albedo.png", "normal.png", "roughness.png", "lod0.glb", "lod1.glb", "mat.ron", "ogg", "wgsl"];
+    let mut names = Vec::new();
+    for folder in folders {
+        for group in groups {
+            for suffix in suffixes {
+                for i in 0..100 {
+                    names.push(format!("{folder}/{group}/item_{i:02}_{suffix}"));
+                }
+            }
+        }
+    }
+    names
+}
+
+/// Sequentially numbered names, like `entity_42`.
+fn sequential() -> Vec<String> {
+    (0..200_000).map(|i| format!("entity_{i}")).collect()
+}
+
+/// Every lowercase name of up to 3 letters.
+fn short_names() -> Vec<String> {
+    let letters = || (b'a'..=b'z').map(char::from);
+    let mut names: Vec<String> = letters().map(String::from).collect();
+    names.extend(letters().flat_map(|a| letters().map(move |b| format!("{a}{b}"))));
+    names.extend(letters().flat_map(|a| letters().flat_map(move |b| letters().map(move |c| format!("{a}{b}{c}")))));
+    names
+}
+
+fn collisions<T: Ord>(mut ids: Vec<T>) -> usize {
+    ids.sort_unstable();
+    ids.windows(2).filter(|pair| pair[0] == pair[1]).count()
+}
+
+/// The expected number of colliding pairs among `n` ideally random ids of `bits` bits.
+fn expected(n: usize, bits: u32) -> f64 {
+    let n = n as f64;
+    n * (n - 1.0) / 2.0 / 2f64.powi(bits as i32)
+}
+
+fn main() {
+    let corpora = [
+        ("system paths", system_paths()),
+        ("asset paths", asset_paths()),
+        ("sequential", sequential()),
+        ("short names", short_names()),
+    ];
+    println!(
+        "{:<14} {:>8} {:>6} {:>14} {:>14} {:>14}",
+        "corpus", "names", "full", "low 32 bits", "high 32 bits", "expected 32"
+    );
+    for (corpus, names) in corpora {
+        let ids: Vec<LabelId> =
+            names.iter().map(|name| ConstLabel::<Names>::new(Box::leak(name.clone().into_boxed_str())).id()).collect();
+        let low = collisions(ids.iter().map(|&id| id as u32).collect());
+        let high = collisions(ids.iter().map(|&id| (id >> (LabelId::BITS - 32)) as u32).collect());
+        let full = collisions(ids);
+        println!("{corpus:<14} {:>8} {full:>6} {low:>14} {high:>14} {:>14.1}", names.len(), expected(names.len(), 32));
+    }
+}
@@ -28,3 +28,55 @@
 pub fn default<T: Default>() -> T {
     Default::default()
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(XaocDefault)]
+    struct Tuple(#[default = 1.5] f32, u8, #[default(Vec::new)] Vec<u8>);
+
+    #[derive(XaocDefault, Debug, PartialEq)]
+    struct Generic<T, U> {
+        value: T,
+        #[default(none)]
+        other: Option<U>,
+    }
+
+    const LIMIT: i64 = 8;
+
+    #[derive(XaocDefault)]
+    struct Expressions {
+        #[default(-1)]
+        negative: i32,
+        #[default(1 + 2)]
+        sum: u8,
+        #[default(Vec::with_capacity(4))]
+        call: Vec<u8>,
+        #[default({ LIMIT })]
+        constant: i64,
+    }
+
+    fn none<T>() -> Option<T> {
+        None
+    }
+
+    #[derive(XaocDefault)]
+    struct Unit;
+
+    /// Not [`Default`], the derive must not require it of `U` as the field sets its own default.
+    #[derive(Debug, PartialEq)]
+    struct NoDefault;
+
+    #[test]
+    fn derive_default() {
+        let tuple: Tuple = default();
+        assert_eq!((tuple.0, tuple.1, tuple.2.len()), (1.5, 0, 0));
+        assert_eq!(Generic::<u32, NoDefault>::default(), Generic { value: 0, other: None });
+        let Unit = default();
+
+        let expressions: Expressions = default();
+        assert_eq!((expressions.negative, expressions.sum, expressions.constant), (-1, 3, LIMIT));
+        assert!(expressions.call.is_empty() && expressions.call.capacity() >= 4);
+    }
+}
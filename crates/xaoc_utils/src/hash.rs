@@ -1,16 +1,33 @@
 use ahash::{AHasher, RandomState};
-use hashbrown::hash_map::RawEntryMut;
-use std::any::{Any, TypeId};
-use std::fmt::Debug;
-use std::hash::{BuildHasher, Hash, Hasher};
-use std::marker::PhantomData;
-use std::ops::Deref;
+use alloc::borrow::ToOwned;
+use core::any::{Any, TypeId};
+use core::borrow::Borrow;
+use core::fmt::Debug;
+use core::hash::{BuildHasher, Hash, Hasher};
+use core::marker::PhantomData;
+use core::ops::Deref;
+use hashbrown::hash_map::{RawEntryMut, RawOccupiedEntryMut, RawVacantEntryMut};
+
+#[cfg(feature = "std")]
+mod concurrent;
+mod memo;
+mod stable;
+mod tree;
+mod type_id;
+#[cfg(feature = "std")]
+pub use concurrent::{ConcurrentHashMap, ConcurrentPreHashMap};
+pub use memo::{Eviction, MemoCache, MemoLimits, MemoStats};
+pub use stable::{stable_hash, StableHasher, StableState};
+pub use tree::HashTree;
+pub use type_id::{combine_type_ids, parse_uuid, type_path_id, StableTypeId};
+pub use xaoc_derive::StableTypeId;
 
 /// A hasher builder that will create a fixed hasher.
+/// The hashes only stay the same within a build, see [`StableHasher`] for hashes that can be persisted.
 #[derive(Debug, Clone, Default)]
 pub struct FixedState;
 
-impl std::hash::BuildHasher for FixedState {
+impl core::hash::BuildHasher for FixedState {
     type Hasher = AHasher;
 
     #[inline]
@@ -37,6 +54,13 @@ pub type HashMap<K, V> = hashbrown::HashMap<K, V, RandomState>;
 /// aHash is designed for performance and is NOT cryptographically secure.
 pub type StableHashMap<K, V> = hashbrown::HashMap<K, V, FixedState>;
 
+/// A hash map iterating in insertion order, which unlike [`StableHashMap`] does not change on rehash,
+/// for outputs that are serialized or diffed, such as debug dumps.
+///
+/// Removing with [`swap_remove`][indexmap::IndexMap::swap_remove] moves the last entry into the hole,
+/// [`shift_remove`][indexmap::IndexMap::shift_remove] keeps the order of the other entries but takes linear time.
+pub type StableIndexMap<K, V> = indexmap::IndexMap<K, V, FixedState>;
+
 /// A [`HashSet`][hashbrown::HashSet] implementing aHash, a high
 /// speed keyed hashing algorithm intended for use in in-memory hashmaps.
 ///
@@ -52,6 +76,9 @@ pub type HashSet<K> = hashbrown::HashSet<K, RandomState>;
 /// aHash is designed for performance and is NOT cryptographically secure.
 pub type StableHashSet<K> = hashbrown::HashSet<K, FixedState>;
 
+/// A hash set iterating in insertion order, see [`StableIndexMap`].
+pub type StableIndexSet<K> = indexmap::IndexSet<K, FixedState>;
+
 /// A pre-hashed value of a specific type. Pre-hashing enables memoization of hashes that are expensive to compute.
 /// It also enables faster [`PartialEq`] comparisons by short circuiting on hash equality.
 /// See [`PassHash`] and [`PassHasher`] for a "pass through" [`BuildHasher`] and [`Hasher`] implementation
@@ -66,10 +93,7 @@ pub struct Hashed<V, H = FixedState> {
 impl<V: Hash, H: BuildHasher + Default> Hashed<V, H> {
     /// Pre-hashes the given value using the [`BuildHasher`] configured in the [`Hashed`] type.
     pub fn new(value: V) -> Self {
-        let builder = H::default();
-        let mut hasher = builder.build_hasher();
-        value.hash(&mut hasher);
-        Self { hash: hasher.finish(), value, marker: PhantomData }
+        Self { hash: H::default().hash_one(&value), value, marker: PhantomData }
     }
 
     /// The pre-computed hash.
@@ -105,7 +129,7 @@ impl<V: PartialEq, H> PartialEq for Hashed<V, H> {
 }
 
 impl<V: Debug, H> Debug for Hashed<V, H> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("Hashed").field("hash", &self.hash).field("value", &self.value).finish()
     }
 }
@@ -119,8 +143,23 @@ impl<V: Clone, H> Clone for Hashed<V, H> {
 
 impl<V: Eq, H> Eq for Hashed<V, H> {}
 
+#[cfg(feature = "serde")]
+impl<V: serde::Serialize, H> serde::Serialize for Hashed<V, H> {
+    /// Serializes only the value, the hash is recomputed on deserialization.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, V: serde::Deserialize<'de> + Hash, H: BuildHasher + Default> serde::Deserialize<'de> for Hashed<V, H> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        V::deserialize(deserializer).map(Self::new)
+    }
+}
+
 /// A [`BuildHasher`] that results in a [`PassHasher`].
-#[derive(Default)]
+#[derive(Debug, Clone, Copy, Default)]
 pub struct PassHash;
 
 impl BuildHasher for PassHash {
@@ -150,6 +189,12 @@ impl Hasher for PassHasher {
     fn write_u64(&mut self, i: u64) {
         self.hash = i;
     }
+
+    /// Folds 128-bit hashes such as wide label ids, both halves are already well distributed.
+    #[inline]
+    fn write_u128(&mut self, i: u128) {
+        self.hash = (i as u64) ^ ((i >> 64) as u64);
+    }
 }
 
 /// A [`HashMap`] pre-configured to use pre-hashed u64 keys and [`PassHash`] passthrough hashing.
@@ -158,12 +203,141 @@ pub type PassHashMap<V> = hashbrown::HashMap<u64, V, PassHash>;
 /// A [`HashMap`] pre-configured to use [`Hashed`] keys and [`PassHash`] passthrough hashing.
 pub type PreHashMap<K, V> = hashbrown::HashMap<Hashed<K>, V, PassHash>;
 
+/// A [`BuildHasher`] for integer keys such as entity ids and indices, see [`IntHasher`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IntHash;
+
+impl BuildHasher for IntHash {
+    type Hasher = IntHasher;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        IntHasher::default()
+    }
+}
+
+/// A [`Hasher`] for integers, mixing each with a single multiplication. Unlike [`PassHasher`] it spreads
+/// sequential keys over the 7 highest bits, which hashbrown uses to tell entries apart within a group.
+/// Also unlike [`PassHasher`], keys may be made of several integers, like tuples.
+#[derive(Debug, Default)]
+pub struct IntHasher {
+    hash: u64,
+}
+
+impl IntHasher {
+    #[inline]
+    fn mix(&mut self, i: u64) {
+        self.hash = (self.hash.rotate_left(26) ^ i).wrapping_mul(0x9e3779b97f4a7c15);
+    }
+}
+
+impl Hasher for IntHasher {
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+
+    fn write(&mut self, _bytes: &[u8]) {
+        panic!("can only hash integers using IntHasher");
+    }
+
+    #[inline]
+    fn write_u8(&mut self, i: u8) {
+        self.mix(i as u64);
+    }
+
+    #[inline]
+    fn write_u16(&mut self, i: u16) {
+        self.mix(i as u64);
+    }
+
+    #[inline]
+    fn write_u32(&mut self, i: u32) {
+        self.mix(i as u64);
+    }
+
+    #[inline]
+    fn write_u64(&mut self, i: u64) {
+        self.mix(i);
+    }
+
+    #[inline]
+    fn write_u128(&mut self, i: u128) {
+        self.mix(i as u64);
+        self.mix((i >> 64) as u64);
+    }
+
+    #[inline]
+    fn write_usize(&mut self, i: usize) {
+        self.mix(i as u64);
+    }
+}
+
+/// A [`HashMap`] pre-configured to use integer keys and [`IntHash`] hashing.
+pub type IntMap<K, V> = hashbrown::HashMap<K, V, IntHash>;
+
+/// A [`HashSet`] pre-configured to use integer keys and [`IntHash`] hashing.
+pub type IntSet<K> = hashbrown::HashSet<K, IntHash>;
+
+/// A [`HashMap`] keyed by [`TypeId`], which is already a hash and is only mixed by [`IntHash`] instead of being
+/// hashed again.
+pub type TypeIdMap<V> = hashbrown::HashMap<TypeId, V, IntHash>;
+
 /// Extension methods intended to add functionality to [`PreHashMap`].
+///
+/// The `*_hashed` methods probe with a borrowed form of the key, such as a `&str` for `Hashed<String>` keys
+/// or a slice for `Hashed<Vec<T>>` keys, and its pre-computed hash, which must be [`fixed_hash`] of it.
+/// Only [`HashedVacantEntry::insert`] allocates an owned key.
+/// ```
+/// use xaoc_utils::hash::{fixed_hash, Hashed, PreHashMap, PreHashMapExt};
+///
+/// let mut shaders = PreHashMap::<String, u32>::default();
+/// shaders.insert(Hashed::new("lit.wgsl".to_string()), 1);
+///
+/// let name = "lit.wgsl";
+/// let hash = fixed_hash(name);
+/// assert_eq!(shaders.get_hashed(hash, name), Some(&1));
+/// *shaders.entry_hashed(fixed_hash("unlit.wgsl"), "unlit.wgsl").or_insert(0) += 2;
+/// assert_eq!(shaders.get_hashed(fixed_hash("unlit.wgsl"), "unlit.wgsl"), Some(&2));
+/// ```
 pub trait PreHashMapExt<K, V> {
     /// Tries to get or insert the value for the given `key` using the pre-computed hash first.
     /// If the [`PreHashMap`] does not already contain the `key`, it will clone it and insert
     /// the value returned by `func`.
     fn get_or_insert_with<F: FnOnce() -> V>(&mut self, key: &Hashed<K>, func: F) -> &mut V;
+
+    /// Returns the value of the borrowed `key` with the pre-computed `hash`.
+    fn get_hashed<Q: ?Sized + Eq>(&self, hash: u64, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>;
+
+    /// Returns the value of the borrowed `key` with the pre-computed `hash`.
+    fn get_mut_hashed<Q: ?Sized + Eq>(&mut self, hash: u64, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>;
+
+    /// Returns `true` if the map contains the borrowed `key` with the pre-computed `hash`.
+    fn contains_hashed<Q: ?Sized + Eq>(&self, hash: u64, key: &Q) -> bool
+    where
+        K: Borrow<Q>;
+
+    /// Removes the borrowed `key` with the pre-computed `hash` and returns its value.
+    fn remove_hashed<Q: ?Sized + Eq>(&mut self, hash: u64, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>;
+
+    /// Returns the entry of the borrowed `key` with the pre-computed `hash`,
+    /// the key is only converted into an owned one when inserting into a vacant entry.
+    fn entry_hashed<'a, 'q, Q: ?Sized + Eq + ToOwned<Owned = K>>(
+        &'a mut self,
+        hash: u64,
+        key: &'q Q,
+    ) -> HashedEntry<'a, 'q, K, Q, V>
+    where
+        K: Borrow<Q>;
+
+    /// Removes the entry with the pre-computed `hash` whose key and value match `predicate`, and returns it.
+    /// This finds an entry from its value, such as an index, when the key is not at hand.
+    fn remove_hashed_where<F: FnMut(&K, &V) -> bool>(&mut self, hash: u64, predicate: F) -> Option<(Hashed<K>, V)>;
 }
 
 impl<K: Hash + Eq + PartialEq + Clone, V> PreHashMapExt<K, V> for PreHashMap<K, V> {
@@ -178,12 +352,182 @@ impl<K: Hash + Eq + PartialEq + Clone, V> PreHashMapExt<K, V> for PreHashMap<K,
             }
         }
     }
+
+    #[inline]
+    fn get_hashed<Q: ?Sized + Eq>(&self, hash: u64, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        self.raw_entry().from_hash(hash, |hashed| hashed.is(hash, key)).map(|(_, value)| value)
+    }
+
+    #[inline]
+    fn get_mut_hashed<Q: ?Sized + Eq>(&mut self, hash: u64, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+    {
+        match self.raw_entry_mut().from_hash(hash, |hashed| hashed.is(hash, key)) {
+            RawEntryMut::Occupied(entry) => Some(entry.into_mut()),
+            RawEntryMut::Vacant(_) => None,
+        }
+    }
+
+    #[inline]
+    fn contains_hashed<Q: ?Sized + Eq>(&self, hash: u64, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.get_hashed(hash, key).is_some()
+    }
+
+    #[inline]
+    fn remove_hashed<Q: ?Sized + Eq>(&mut self, hash: u64, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+    {
+        match self.raw_entry_mut().from_hash(hash, |hashed| hashed.is(hash, key)) {
+            RawEntryMut::Occupied(entry) => Some(entry.remove()),
+            RawEntryMut::Vacant(_) => None,
+        }
+    }
+
+    #[inline]
+    fn entry_hashed<'a, 'q, Q: ?Sized + Eq + ToOwned<Owned = K>>(
+        &'a mut self,
+        hash: u64,
+        key: &'q Q,
+    ) -> HashedEntry<'a, 'q, K, Q, V>
+    where
+        K: Borrow<Q>,
+    {
+        match self.raw_entry_mut().from_hash(hash, |hashed| hashed.is(hash, key)) {
+            RawEntryMut::Occupied(entry) => HashedEntry::Occupied(HashedOccupiedEntry { entry }),
+            RawEntryMut::Vacant(entry) => HashedEntry::Vacant(HashedVacantEntry { entry, hash, key }),
+        }
+    }
+
+    #[inline]
+    fn remove_hashed_where<F: FnMut(&K, &V) -> bool>(&mut self, hash: u64, mut predicate: F) -> Option<(Hashed<K>, V)> {
+        // The raw entry API only shows the keys, the value of each candidate is found by its key.
+        let (found, _) = self.raw_entry().from_hash(hash, |hashed| {
+            hashed.hash == hash
+                && self.get_hashed(hash, &hashed.value).is_some_and(|value| predicate(&hashed.value, value))
+        })?;
+        let found: *const Hashed<K> = found;
+        match self.raw_entry_mut().from_hash(hash, |hashed| core::ptr::eq(hashed, found)) {
+            RawEntryMut::Occupied(entry) => Some(entry.remove_entry()),
+            RawEntryMut::Vacant(_) => None,
+        }
+    }
+}
+
+impl<K, H> Hashed<K, H> {
+    #[inline]
+    pub(crate) fn is<Q: ?Sized + Eq>(&self, hash: u64, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.hash == hash && self.value.borrow() == key
+    }
+}
+
+/// An entry of a [`PreHashMap`] found by a borrowed key, see [`PreHashMapExt::entry_hashed`].
+pub enum HashedEntry<'a, 'q, K, Q: ?Sized, V> {
+    Occupied(HashedOccupiedEntry<'a, K, V>),
+    Vacant(HashedVacantEntry<'a, 'q, K, Q, V>),
+}
+
+impl<'a, 'q, K: Hash, Q: ?Sized + ToOwned<Owned = K>, V> HashedEntry<'a, 'q, K, Q, V> {
+    #[inline]
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    #[inline]
+    pub fn or_insert_with<F: FnOnce() -> V>(self, func: F) -> &'a mut V {
+        match self {
+            Self::Occupied(entry) => entry.into_mut(),
+            Self::Vacant(entry) => entry.insert(func()),
+        }
+    }
+
+    #[inline]
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    #[inline]
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, func: F) -> Self {
+        if let Self::Occupied(entry) = &mut self {
+            func(entry.get_mut());
+        }
+        self
+    }
+}
+
+pub struct HashedOccupiedEntry<'a, K, V> {
+    entry: RawOccupiedEntryMut<'a, Hashed<K>, V, PassHash>,
+}
+
+impl<'a, K, V> HashedOccupiedEntry<'a, K, V> {
+    #[inline]
+    pub fn key(&self) -> &Hashed<K> {
+        self.entry.key()
+    }
+
+    #[inline]
+    pub fn get(&self) -> &V {
+        self.entry.get()
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut V {
+        self.entry.get_mut()
+    }
+
+    #[inline]
+    pub fn into_mut(self) -> &'a mut V {
+        self.entry.into_mut()
+    }
+
+    /// Replaces the value and returns the old one.
+    #[inline]
+    pub fn insert(&mut self, value: V) -> V {
+        self.entry.insert(value)
+    }
+
+    #[inline]
+    pub fn remove(self) -> V {
+        self.entry.remove()
+    }
+}
+
+pub struct HashedVacantEntry<'a, 'q, K, Q: ?Sized, V> {
+    entry: RawVacantEntryMut<'a, Hashed<K>, V, PassHash>,
+    hash: u64,
+    key: &'q Q,
+}
+
+impl<'a, 'q, K: Hash, Q: ?Sized + ToOwned<Owned = K>, V> HashedVacantEntry<'a, 'q, K, Q, V> {
+    /// The borrowed key the entry was looked up with.
+    #[inline]
+    pub fn key(&self) -> &'q Q {
+        self.key
+    }
+
+    /// Inserts the value with an owned copy of the key, reusing the pre-computed hash.
+    #[inline]
+    pub fn insert(self, value: V) -> &'a mut V {
+        let key = Hashed { hash: self.hash, value: self.key.to_owned(), marker: PhantomData };
+        self.entry.insert_hashed_nocheck(self.hash, key, value).1
+    }
 }
 
 pub fn fixed_hash<T: ?Sized + Hash>(value: &T) -> u64 {
-    let mut hasher = FixedState.build_hasher();
-    value.hash(&mut hasher);
-    return hasher.finish();
+    FixedState.hash_one(value)
 }
 
 pub fn fixed_hash_with_type<T: Any + Hash>(value: T) -> u64 {
@@ -191,5 +535,123 @@ pub fn fixed_hash_with_type<T: Any + Hash>(value: T) -> u64 {
     value.hash(&mut hasher);
     value.type_id().hash(&mut hasher);
     TypeId::of::<T>().hash(&mut hasher);
-    return hasher.finish();
+    hasher.finish()
+}
+
+/// Like [`fixed_hash_with_type`], but hashes with [`StableHasher`] and identifies the type with [`StableTypeId`],
+/// so that the hash can be persisted and compared between builds.
+pub fn stable_hash_with_type<T: ?Sized + StableTypeId + Hash>(value: &T) -> u64 {
+    let mut hasher = StableHasher::new();
+    value.hash(&mut hasher);
+    T::STABLE_TYPE_ID.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn borrowed_keys() {
+        let mut map = PreHashMap::<String, u32>::default();
+        map.insert(Hashed::new("a".to_string()), 1);
+        assert_eq!(fixed_hash("a"), Hashed::<String>::new("a".to_string()).hash());
+        assert_eq!(map.get_hashed(fixed_hash("a"), "a"), Some(&1));
+        assert_eq!(map.get_hashed(fixed_hash("b"), "b"), None);
+        assert!(!map.contains_hashed(fixed_hash("a"), "b"));
+        *map.get_mut_hashed(fixed_hash("a"), "a").unwrap() += 1;
+
+        *map.entry_hashed(fixed_hash("a"), "a").or_insert(10) += 1;
+        *map.entry_hashed(fixed_hash("b"), "b").and_modify(|value| *value = 0).or_insert(10) += 1;
+        assert_eq!(map.get_or_insert_with(&Hashed::new("b".to_string()), || 0), &11);
+        assert_eq!(map.get_hashed(fixed_hash("a"), "a"), Some(&3));
+        match map.entry_hashed(fixed_hash("a"), "a") {
+            HashedEntry::Occupied(entry) => {
+                assert_eq!(entry.key().as_str(), "a");
+                assert_eq!(entry.remove(), 3);
+            }
+            HashedEntry::Vacant(_) => unreachable!(),
+        }
+        assert_eq!(map.remove_hashed(fixed_hash("b"), "b"), Some(11));
+        assert!(map.is_empty());
+
+        let mut vectors = PreHashMap::<Vec<u8>, &str>::default();
+        let descriptor = [1, 2, 3];
+        vectors.entry_hashed(fixed_hash(&descriptor[..]), &descriptor[..]).or_insert("slice");
+        assert_eq!(vectors.get(&Hashed::new(vec![1, 2, 3])), Some(&"slice"));
+        assert!(vectors.contains_hashed(fixed_hash(&descriptor[..]), &descriptor[..]));
+
+        // Two keys sharing a hash, told apart by their values.
+        let colliding_key = |value| Hashed { hash: 0, value, marker: PhantomData };
+        let mut colliding = PreHashMap::<u32, usize>::default();
+        colliding.insert(colliding_key(1), 10);
+        colliding.insert(colliding_key(2), 20);
+        assert_eq!(colliding.remove_hashed_where(0, |_, &value| value == 30), None);
+        let (key, value) = colliding.remove_hashed_where(0, |_, &value| value == 20).unwrap();
+        assert_eq!((*key, value), (2, 20));
+        assert_eq!(colliding.remove_hashed_where(0, |&key, _| key == 1).map(|(_, value)| value), Some(10));
+        assert!(colliding.is_empty());
+    }
+
+    #[test]
+    fn typed_hashes() {
+        assert_ne!(stable_hash_with_type(&1u32), stable_hash_with_type(&1i32));
+        assert_ne!(stable_hash_with_type(&1u32), stable_hash(&1u32));
+        assert_eq!(stable_hash_with_type("typed"), 0x17567347219acc2f);
+        assert_ne!(fixed_hash_with_type(1u32), fixed_hash_with_type(1i32));
+    }
+
+    #[test]
+    fn int_maps() {
+        let control_bytes: HashSet<_> = (0u32..128).map(|i| IntHash.hash_one(i) >> 57).collect();
+        assert!(control_bytes.len() > 64);
+        assert_ne!(IntHash.hash_one((1u32, 2u32)), IntHash.hash_one((2u32, 1u32)));
+
+        let mut map = IntMap::default();
+        map.extend((0usize..1000).map(|i| (i, i * 2)));
+        assert_eq!(map[&500], 1000);
+        let set: IntSet<i64> = [-1, 0, 1].into_iter().collect();
+        assert!(set.contains(&-1));
+
+        let mut types = TypeIdMap::default();
+        types.insert(TypeId::of::<u32>(), "u32");
+        types.insert(TypeId::of::<String>(), "String");
+        assert_eq!(types[&TypeId::of::<u32>()], "u32");
+    }
+
+    #[test]
+    fn insertion_order() {
+        let mut map = StableIndexMap::default();
+        for key in (0..100).rev() {
+            map.insert(key, key * 2);
+        }
+        assert!(map.keys().copied().eq((0..100).rev()));
+        assert_eq!(map.swap_remove(&99), Some(198));
+        assert_eq!(map.first(), Some((&0, &0)));
+        assert_eq!(map.shift_remove(&98), Some(196));
+        assert!(map.keys().skip(1).copied().eq((1..98).rev()));
+
+        let mut set = StableIndexSet::default();
+        set.extend(["b", "a", "c"]);
+        set.shift_remove("a");
+        assert!(set.iter().eq(&["b", "c"]));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn hashed_round_trip() {
+        let hashed = Hashed::<String>::new("value".into());
+        let json = serde_json::to_string(&hashed).unwrap();
+        assert_eq!(json, "\"value\"");
+        let restored: Hashed<String> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.hash(), hashed.hash());
+
+        let mut map = PreHashMap::default();
+        map.insert(hashed, 1u32);
+        let restored: PreHashMap<String, u32> = serde_json::from_str(&serde_json::to_string(&map).unwrap()).unwrap();
+        assert_eq!(restored, map);
+
+        let ordered: StableIndexMap<_, _> = [("z", 1), ("a", 2)].into_iter().collect();
+        assert_eq!(serde_json::to_string(&ordered).unwrap(), r#"{"z":1,"a":2}"#);
+    }
 }
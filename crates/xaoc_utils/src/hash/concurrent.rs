@@ -0,0 +1,247 @@
+use super::{Hashed, PassHash};
+use ahash::RandomState;
+use core::borrow::Borrow;
+use core::fmt::{Debug, Formatter};
+use core::hash::{BuildHasher, Hash};
+use hashbrown::hash_map::RawEntryMut;
+use parking_lot::{MappedRwLockReadGuard, MappedRwLockWriteGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// A hash map for many threads, split into shards that are each a [`hashbrown::HashMap`] behind its own
+/// [`RwLock`], so that threads accessing different keys rarely wait for each other.
+///
+/// A key is hashed once with `S`, the hash picks its shard and is reused within the shard.
+/// Values are accessed through guards holding the lock of their shard, so a guard must not be held
+/// across other calls that may write to the same map, as that can deadlock.
+pub struct ConcurrentHashMap<K, V, S = RandomState> {
+    shards: Box<[RwLock<hashbrown::HashMap<K, V, S>>]>,
+    /// Shift selecting the shard from the bits of the hash below the 7 highest, which hashbrown
+    /// uses within each shard.
+    shift: u32,
+    hasher: S,
+}
+
+/// A [`ConcurrentHashMap`] pre-configured to use [`Hashed`] keys, routed to their shard by their pre-computed hash.
+pub type ConcurrentPreHashMap<K, V> = ConcurrentHashMap<Hashed<K>, V, PassHash>;
+
+impl<K, V, S: BuildHasher + Clone + Default> ConcurrentHashMap<K, V, S> {
+    /// Creates a map with a number of shards suited to the number of threads of the machine.
+    pub fn new() -> Self {
+        Self::with_hasher(S::default())
+    }
+
+    /// Creates a map with `shards` shards, rounded up to a power of two.
+    pub fn with_shards(shards: usize) -> Self {
+        Self::with_shards_and_hasher(shards, S::default())
+    }
+}
+
+impl<K, V, S: BuildHasher + Clone> ConcurrentHashMap<K, V, S> {
+    pub fn with_hasher(hasher: S) -> Self {
+        let threads = std::thread::available_parallelism().map_or(1, usize::from);
+        Self::with_shards_and_hasher(threads * 4, hasher)
+    }
+
+    pub fn with_shards_and_hasher(shards: usize, hasher: S) -> Self {
+        let shards = shards.max(1).next_power_of_two();
+        Self {
+            shards: (0..shards).map(|_| RwLock::new(hashbrown::HashMap::with_hasher(hasher.clone()))).collect(),
+            shift: 64 - shards.trailing_zeros(),
+            hasher,
+        }
+    }
+
+    #[inline]
+    fn shard(&self, hash: u64) -> &RwLock<hashbrown::HashMap<K, V, S>> {
+        let index = (hash << 7).checked_shr(self.shift).unwrap_or(0);
+        &self.shards[index as usize]
+    }
+
+    /// The number of shards.
+    pub fn shards(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// The number of entries. Entries may be inserted or removed concurrently while they are counted.
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(|shard| shard.read().len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shards.iter().all(|shard| shard.read().is_empty())
+    }
+
+    pub fn clear(&self) {
+        for shard in self.shards.iter() {
+            shard.write().clear();
+        }
+    }
+
+    /// Keeps only the entries for which `func` returns `true`, locking one shard at a time.
+    pub fn retain<F: FnMut(&K, &mut V) -> bool>(&self, mut func: F) {
+        for shard in self.shards.iter() {
+            shard.write().retain(|key, value| func(key, value));
+        }
+    }
+
+    /// Calls `func` with every entry, locking one shard at a time.
+    pub fn for_each<F: FnMut(&K, &V)>(&self, mut func: F) {
+        for shard in self.shards.iter() {
+            shard.read().iter().for_each(|(key, value)| func(key, value));
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Clone> ConcurrentHashMap<K, V, S> {
+    pub fn get<Q: ?Sized + Hash + Eq>(&self, key: &Q) -> Option<MappedRwLockReadGuard<'_, V>>
+    where
+        K: Borrow<Q>,
+    {
+        let hash = self.hasher.hash_one(key);
+        let shard = self.shard(hash).read();
+        RwLockReadGuard::try_map(shard, |map| find(map, hash, key)).ok()
+    }
+
+    pub fn get_mut<Q: ?Sized + Hash + Eq>(&self, key: &Q) -> Option<MappedRwLockWriteGuard<'_, V>>
+    where
+        K: Borrow<Q>,
+    {
+        let hash = self.hasher.hash_one(key);
+        let shard = self.shard(hash).write();
+        RwLockWriteGuard::try_map(shard, |map| match map.raw_entry_mut().from_key_hashed_nocheck(hash, key) {
+            RawEntryMut::Occupied(entry) => Some(entry.into_mut()),
+            RawEntryMut::Vacant(_) => None,
+        })
+        .ok()
+    }
+
+    pub fn contains_key<Q: ?Sized + Hash + Eq>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Inserts the value and returns the previous value of the key.
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        let hash = self.hasher.hash_one(&key);
+        let mut shard = self.shard(hash).write();
+        match shard.raw_entry_mut().from_key_hashed_nocheck(hash, &key) {
+            RawEntryMut::Occupied(mut entry) => Some(entry.insert(value)),
+            RawEntryMut::Vacant(entry) => {
+                entry.insert_hashed_nocheck(hash, key, value);
+                None
+            }
+        }
+    }
+
+    pub fn remove<Q: ?Sized + Hash + Eq>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+    {
+        let hash = self.hasher.hash_one(key);
+        match self.shard(hash).write().raw_entry_mut().from_key_hashed_nocheck(hash, key) {
+            RawEntryMut::Occupied(entry) => Some(entry.remove()),
+            RawEntryMut::Vacant(_) => None,
+        }
+    }
+
+    /// Tries to get the value for the given `key` under a shared lock first. If the map does not contain
+    /// the `key` yet, it will clone it and insert the value returned by `func`, like
+    /// [`PreHashMapExt::get_or_insert_with`][super::PreHashMapExt::get_or_insert_with].
+    ///
+    /// `func` runs at most once per key even when threads race for it, while the shard is locked,
+    /// so it must not access the map.
+    pub fn get_or_insert_with<F: FnOnce() -> V>(&self, key: &K, func: F) -> MappedRwLockReadGuard<'_, V>
+    where
+        K: Clone,
+    {
+        let hash = self.hasher.hash_one(key);
+        let shard = self.shard(hash);
+        let map = match RwLockReadGuard::try_map(shard.read(), |map| find(map, hash, key)) {
+            Ok(value) => return value,
+            Err(map) => map,
+        };
+        drop(map);
+
+        let mut map = shard.write();
+        if let RawEntryMut::Vacant(entry) = map.raw_entry_mut().from_key_hashed_nocheck(hash, key) {
+            entry.insert_hashed_nocheck(hash, key.clone(), func());
+        }
+        RwLockReadGuard::map(RwLockWriteGuard::downgrade(map), |map| find(map, hash, key).unwrap())
+    }
+}
+
+#[inline]
+fn find<'a, K: Borrow<Q>, Q: ?Sized + Eq, V, S>(
+    map: &'a hashbrown::HashMap<K, V, S>,
+    hash: u64,
+    key: &Q,
+) -> Option<&'a V> {
+    map.raw_entry().from_key_hashed_nocheck(hash, key).map(|(_, value)| value)
+}
+
+impl<K, V, S: BuildHasher + Clone + Default> Default for ConcurrentHashMap<K, V, S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Debug, V: Debug, S: BuildHasher + Clone> Debug for ConcurrentHashMap<K, V, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let mut map = f.debug_map();
+        for shard in self.shards.iter() {
+            map.entries(shard.read().iter());
+        }
+        map.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::FixedState;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn concurrent_map() {
+        let map = ConcurrentHashMap::<String, u32, FixedState>::with_shards(3);
+        assert_eq!(map.shards(), 4);
+        assert_eq!(map.insert("a".to_string(), 1), None);
+        assert_eq!(map.insert("a".to_string(), 2), Some(1));
+        assert_eq!(map.get("a").as_deref(), Some(&2));
+        *map.get_mut("a").unwrap() += 1;
+        assert_eq!(*map.get_or_insert_with(&"a".to_string(), || unreachable!()), 3);
+        assert_eq!(*map.get_or_insert_with(&"b".to_string(), || 4), 4);
+        assert_eq!(map.len(), 2);
+        map.retain(|key, _| key == "b");
+        assert!(!map.contains_key("a"));
+        assert_eq!(map.remove("b"), Some(4));
+        assert!(map.is_empty());
+
+        let single = ConcurrentHashMap::<u32, u32>::with_shards(1);
+        single.insert(1, 1);
+        assert_eq!(single.get(&1).as_deref(), Some(&1));
+    }
+
+    #[test]
+    fn threads() {
+        let map = ConcurrentPreHashMap::<u32, u32>::default();
+        let calls = AtomicUsize::new(0);
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    for i in 0..1000 {
+                        let value = map.get_or_insert_with(&Hashed::new(i), || {
+                            calls.fetch_add(1, Ordering::Relaxed);
+                            i * 2
+                        });
+                        assert_eq!(*value, i * 2);
+                    }
+                });
+            }
+        });
+        assert_eq!(calls.load(Ordering::Relaxed), 1000);
+        assert_eq!(map.len(), 1000);
+        assert_eq!(map.get(&Hashed::new(10)).as_deref(), Some(&20));
+    }
+}
@@ -0,0 +1,506 @@
+use super::{Hashed, PreHashMap, PreHashMapExt};
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::fmt::{Debug, Formatter};
+use core::hash::Hash;
+
+const NIL: usize = usize::MAX;
+
+/// How a [`MemoCache`] picks the entry to evict when it is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Eviction {
+    /// Evicts the least recently used entry.
+    #[default]
+    Lru,
+    /// Approximates [`Eviction::Lru`] by sweeping over the entries and evicting the first one not used
+    /// since the previous sweep, which makes a hit cheaper as it only sets a flag.
+    Clock,
+}
+
+/// The limits of a [`MemoCache`], exceeding any of them evicts entries.
+///
+/// The entry inserted last is always kept, so that [`MemoCache::get_or_insert_with`] can return it, even if it
+/// exceeds a limit on its own: with `entries: 0`, or with an entry larger than `bytes`, the cache holds that one
+/// entry until the next insertion evicts it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoLimits {
+    /// The maximum number of entries.
+    pub entries: usize,
+    /// The maximum total size of the entries, as measured by the size function of the cache.
+    pub bytes: usize,
+    /// Entries not used in this many frames are removed by [`MemoCache::advance_frame`].
+    pub frames: Option<u64>,
+    pub eviction: Eviction,
+}
+
+impl Default for MemoLimits {
+    /// No limits, with [`Eviction::Lru`].
+    fn default() -> Self {
+        Self { entries: usize::MAX, bytes: usize::MAX, frames: None, eviction: Eviction::Lru }
+    }
+}
+
+/// Statistics of a [`MemoCache`], since it was created or since [`MemoCache::reset_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MemoStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Entries removed to stay within the entry and byte limits.
+    pub evictions: u64,
+    /// Entries removed by [`MemoCache::advance_frame`].
+    pub expirations: u64,
+}
+
+impl MemoStats {
+    /// The fraction of lookups that were hits, or 0 if there were none.
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+/// An entry of a [`MemoCache`], whose key is only stored in the map.
+struct Entry<V> {
+    /// The pre-computed hash of the key.
+    hash: u64,
+    value: V,
+    bytes: usize,
+    /// The last frame in which the entry was used.
+    frame: u64,
+    /// Whether the entry was used since the clock hand passed it.
+    referenced: bool,
+    prev: usize,
+    next: usize,
+}
+
+/// A memoization cache for [`Hashed`] keys, which unlike [`PreHashMapExt::get_or_insert_with`]
+/// does not grow without bound: it evicts entries to stay within its [`MemoLimits`] and can expire entries
+/// that were not used for a number of frames. Each key is stored once, and only cloned when
+/// [`get_or_insert_with`][MemoCache::get_or_insert_with] misses.
+///
+/// [`PreHashMapExt::get_or_insert_with`]: super::PreHashMapExt::get_or_insert_with
+///
+/// ```
+/// use xaoc_utils::hash::{Eviction, Hashed, MemoCache, MemoLimits};
+///
+/// let limits = MemoLimits { entries: 2, frames: Some(1), eviction: Eviction::Lru, ..Default::default() };
+/// let mut layouts = MemoCache::<&str, u32>::new(limits);
+/// layouts.get_or_insert_with(&Hashed::new("a"), || 1);
+/// layouts.get_or_insert_with(&Hashed::new("b"), || 2);
+/// layouts.get_or_insert_with(&Hashed::new("a"), || unreachable!());
+/// layouts.get_or_insert_with(&Hashed::new("c"), || 3);
+/// assert_eq!(layouts.get(&Hashed::new("b")), None);
+/// assert_eq!(layouts.stats().evictions, 1);
+///
+/// layouts.advance_frame();
+/// layouts.advance_frame();
+/// assert!(layouts.is_empty());
+/// ```
+pub struct MemoCache<K, V> {
+    /// The index of the entry of each key.
+    map: PreHashMap<K, usize>,
+    entries: Vec<Option<Entry<V>>>,
+    free: Vec<usize>,
+    /// The most and the least recently used entries, for [`Eviction::Lru`].
+    head: usize,
+    tail: usize,
+    /// The next entry considered for eviction, for [`Eviction::Clock`].
+    hand: usize,
+    limits: MemoLimits,
+    size_of: fn(&K, &V) -> usize,
+    bytes: usize,
+    frame: u64,
+    stats: MemoStats,
+}
+
+impl<K, V> MemoCache<K, V> {
+    /// Creates a cache measuring each entry by the size of its key and value types,
+    /// excluding memory they own, see [`MemoCache::with_size_of`].
+    pub fn new(limits: MemoLimits) -> Self {
+        Self::with_size_of(limits, |_, _| core::mem::size_of::<(Hashed<K>, V)>())
+    }
+
+    /// Creates a cache measuring each entry with `size_of` when it is inserted, for [`MemoLimits::bytes`].
+    pub fn with_size_of(limits: MemoLimits, size_of: fn(&K, &V) -> usize) -> Self {
+        Self {
+            map: PreHashMap::default(),
+            entries: Vec::new(),
+            free: Vec::new(),
+            head: NIL,
+            tail: NIL,
+            hand: 0,
+            limits,
+            size_of,
+            bytes: 0,
+            frame: 0,
+            stats: MemoStats::default(),
+        }
+    }
+
+    pub fn limits(&self) -> &MemoLimits {
+        &self.limits
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// The total size of the entries, as measured when they were inserted.
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// The current frame, see [`MemoCache::advance_frame`].
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    pub fn stats(&self) -> &MemoStats {
+        &self.stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = MemoStats::default();
+    }
+
+    /// Removes all entries, without counting them as evicted.
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.entries.clear();
+        self.free.clear();
+        self.head = NIL;
+        self.tail = NIL;
+        self.hand = 0;
+        self.bytes = 0;
+    }
+
+    #[inline]
+    fn entry(&mut self, index: usize) -> &mut Entry<V> {
+        self.entries[index].as_mut().expect("memo cache index of a removed entry")
+    }
+
+    fn touch(&mut self, index: usize) {
+        let frame = self.frame;
+        let entry = self.entry(index);
+        entry.frame = frame;
+        entry.referenced = true;
+        if self.limits.eviction == Eviction::Lru && self.head != index {
+            self.unlink(index);
+            self.link_front(index);
+        }
+    }
+
+    fn link_front(&mut self, index: usize) {
+        let head = self.head;
+        let entry = self.entry(index);
+        entry.prev = NIL;
+        entry.next = head;
+        match head {
+            NIL => self.tail = index,
+            head => self.entry(head).prev = index,
+        }
+        self.head = index;
+    }
+
+    fn unlink(&mut self, index: usize) {
+        let entry = self.entry(index);
+        let (prev, next) = (entry.prev, entry.next);
+        match prev {
+            NIL => self.head = next,
+            prev => self.entry(prev).next = next,
+        }
+        match next {
+            NIL => self.tail = prev,
+            next => self.entry(next).prev = prev,
+        }
+    }
+
+    /// Picks the entry to evict, the cache must not be empty.
+    fn victim(&mut self) -> usize {
+        match self.limits.eviction {
+            Eviction::Lru => self.tail,
+            Eviction::Clock => loop {
+                if self.hand >= self.entries.len() {
+                    self.hand = 0;
+                }
+                let index = self.hand;
+                self.hand += 1;
+                if let Some(entry) = &mut self.entries[index] {
+                    if !entry.referenced {
+                        return index;
+                    }
+                    entry.referenced = false;
+                }
+            },
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> MemoCache<K, V> {
+    /// The index of the entry of the borrowed `key` with the pre-computed `hash`.
+    #[inline]
+    fn find<Q: ?Sized + Eq>(&self, hash: u64, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+    {
+        self.map.get_hashed(hash, key).copied()
+    }
+
+    /// Tries to get the value for the given `key` using the pre-computed hash first. If the cache does not
+    /// contain the `key`, it will clone it and insert the value returned by `func`, evicting other entries
+    /// if needed.
+    ///
+    /// Changes to the value through the returned reference are not measured for [`MemoLimits::bytes`].
+    pub fn get_or_insert_with<F: FnOnce() -> V>(&mut self, key: &Hashed<K>, func: F) -> &mut V {
+        let index = match self.find(key.hash(), &**key) {
+            Some(index) => {
+                self.stats.hits += 1;
+                self.touch(index);
+                index
+            }
+            None => {
+                self.stats.misses += 1;
+                self.insert_new(key.clone(), func())
+            }
+        };
+        &mut self.entry(index).value
+    }
+
+    /// Returns the value of the `key`, counting as a use of the entry.
+    pub fn get(&mut self, key: &Hashed<K>) -> Option<&V> {
+        self.get_hashed(key.hash(), &**key)
+    }
+
+    /// Returns the value of the borrowed `key` with the pre-computed `hash`, see [`PreHashMapExt`][super::PreHashMapExt],
+    /// counting as a use of the entry.
+    pub fn get_hashed<Q: ?Sized + Eq>(&mut self, hash: u64, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        match self.find(hash, key) {
+            Some(index) => {
+                self.stats.hits += 1;
+                self.touch(index);
+                Some(&self.entry(index).value)
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Returns `true` if the cache contains the `key`, without counting as a use of the entry.
+    pub fn contains(&self, key: &Hashed<K>) -> bool {
+        self.find(key.hash(), &**key).is_some()
+    }
+
+    /// Inserts the value, evicting other entries if needed, and returns the previous value of the key.
+    pub fn insert(&mut self, key: Hashed<K>, value: V) -> Option<V> {
+        let previous = self.remove_hashed(key.hash(), &*key);
+        self.insert_new(key, value);
+        previous
+    }
+
+    /// Removes the borrowed `key` with the pre-computed `hash` and returns its value.
+    pub fn remove_hashed<Q: ?Sized + Eq>(&mut self, hash: u64, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+    {
+        let index = self.find(hash, key)?;
+        Some(self.remove_index(index))
+    }
+
+    /// Starts a new frame, removing the entries not used in the last [`MemoLimits::frames`] frames.
+    /// This visits every entry.
+    pub fn advance_frame(&mut self) {
+        self.frame += 1;
+        let Some(frames) = self.limits.frames else {
+            return;
+        };
+        for index in 0..self.entries.len() {
+            if matches!(&self.entries[index], Some(entry) if self.frame - entry.frame > frames) {
+                self.remove_index(index);
+                self.stats.expirations += 1;
+            }
+        }
+    }
+
+    fn insert_new(&mut self, key: Hashed<K>, value: V) -> usize {
+        let bytes = (self.size_of)(&key, &value);
+        while !self.is_empty()
+            && (self.len() >= self.limits.entries || self.bytes.saturating_add(bytes) > self.limits.bytes)
+        {
+            let index = self.victim();
+            self.remove_index(index);
+            self.stats.evictions += 1;
+        }
+
+        let entry =
+            Entry { hash: key.hash(), value, bytes, frame: self.frame, referenced: false, prev: NIL, next: NIL };
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.entries[index] = Some(entry);
+                index
+            }
+            None => {
+                self.entries.push(Some(entry));
+                self.entries.len() - 1
+            }
+        };
+        self.map.insert(key, index);
+        self.bytes += bytes;
+        if self.limits.eviction == Eviction::Lru {
+            self.link_front(index);
+        }
+        index
+    }
+
+    fn remove_index(&mut self, index: usize) -> V {
+        if self.limits.eviction == Eviction::Lru {
+            self.unlink(index);
+        }
+        let entry = self.entries[index].take().expect("memo cache index of a removed entry");
+        self.map.remove_hashed_where(entry.hash, |_, &slot| slot == index);
+        self.free.push(index);
+        self.bytes -= entry.bytes;
+        entry.value
+    }
+}
+
+impl<K, V> Default for MemoCache<K, V> {
+    fn default() -> Self {
+        Self::new(MemoLimits::default())
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for MemoCache<K, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let entries = self.map.iter().filter_map(|(key, &index)| Some((key, &self.entries[index].as_ref()?.value)));
+        f.debug_map().entries(entries).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::fixed_hash;
+
+    fn cache(limits: MemoLimits) -> MemoCache<u32, u32> {
+        MemoCache::with_size_of(limits, |_, value| *value as usize)
+    }
+
+    #[test]
+    fn lru() {
+        let mut memo = cache(MemoLimits { entries: 3, ..Default::default() });
+        for key in 0..3 {
+            memo.get_or_insert_with(&Hashed::new(key), || key);
+        }
+        assert_eq!(memo.get(&Hashed::new(0)), Some(&0));
+        memo.insert(Hashed::new(3), 3);
+        assert!(!memo.contains(&Hashed::new(1)));
+        memo.insert(Hashed::new(4), 4);
+        assert!(!memo.contains(&Hashed::new(2)));
+        assert_eq!(memo.len(), 3);
+        assert_eq!(memo.remove_hashed(fixed_hash(&0), &0), Some(0));
+        assert_eq!(memo.bytes(), 7);
+        assert_eq!(*memo.stats(), MemoStats { hits: 1, misses: 3, evictions: 2, expirations: 0 });
+        assert_eq!(memo.stats().hit_rate(), 0.25);
+    }
+
+    #[test]
+    fn clock() {
+        let mut memo = cache(MemoLimits { entries: 3, eviction: Eviction::Clock, ..Default::default() });
+        for key in 0..3 {
+            memo.insert(Hashed::new(key), key);
+        }
+        memo.get(&Hashed::new(0));
+        memo.get(&Hashed::new(2));
+        memo.insert(Hashed::new(3), 3);
+        assert!(!memo.contains(&Hashed::new(1)));
+        memo.insert(Hashed::new(4), 4);
+        assert!(!memo.contains(&Hashed::new(0)));
+        assert!(memo.contains(&Hashed::new(3)));
+        assert_eq!(memo.len(), 3);
+    }
+
+    #[test]
+    fn byte_limit() {
+        let mut memo = cache(MemoLimits { bytes: 10, ..Default::default() });
+        memo.insert(Hashed::new(0), 4);
+        memo.insert(Hashed::new(1), 4);
+        memo.insert(Hashed::new(2), 4);
+        assert_eq!((memo.len(), memo.bytes()), (2, 8));
+        assert!(!memo.contains(&Hashed::new(0)));
+        assert_eq!(memo.insert(Hashed::new(1), 2), Some(4));
+        assert_eq!(memo.bytes(), 6);
+        memo.insert(Hashed::new(3), 20);
+        assert_eq!((memo.len(), memo.bytes()), (1, 20));
+    }
+
+    #[test]
+    fn over_limits() {
+        let mut memo = cache(MemoLimits { entries: 0, ..Default::default() });
+        assert_eq!(*memo.get_or_insert_with(&Hashed::new(0), || 1), 1);
+        assert_eq!(memo.len(), 1);
+        memo.insert(Hashed::new(1), 1);
+        assert_eq!(memo.len(), 1);
+        assert!(memo.contains(&Hashed::new(1)));
+        assert_eq!(memo.stats().evictions, 1);
+
+        let mut memo = cache(MemoLimits { bytes: 10, ..Default::default() });
+        memo.insert(Hashed::new(0), 4);
+        assert_eq!(*memo.get_or_insert_with(&Hashed::new(1), || 20), 20);
+        assert_eq!((memo.len(), memo.bytes()), (1, 20));
+        memo.insert(Hashed::new(2), 4);
+        assert_eq!((memo.len(), memo.bytes()), (1, 4));
+    }
+
+    #[test]
+    fn keys_cloned_once() {
+        #[derive(PartialEq, Eq, Hash)]
+        struct Key(u32);
+
+        static CLONES: core::sync::atomic::AtomicUsize = core::sync::atomic::AtomicUsize::new(0);
+        impl Clone for Key {
+            fn clone(&self) -> Self {
+                CLONES.fetch_add(1, core::sync::atomic::Ordering::Relaxed);
+                Key(self.0)
+            }
+        }
+
+        let mut memo = MemoCache::<Key, u32>::new(MemoLimits { entries: 2, ..Default::default() });
+        for key in [0, 1, 0, 2, 1] {
+            memo.get_or_insert_with(&Hashed::new(Key(key)), || key);
+        }
+        memo.insert(Hashed::new(Key(3)), 3);
+        assert_eq!(CLONES.load(core::sync::atomic::Ordering::Relaxed), 4);
+        assert_eq!(memo.len(), 2);
+        assert_eq!(memo.get_hashed(fixed_hash(&Key(3)), &Key(3)), Some(&3));
+    }
+
+    #[test]
+    fn expiry() {
+        let mut memo = cache(MemoLimits { frames: Some(1), ..Default::default() });
+        memo.insert(Hashed::new(0), 0);
+        memo.insert(Hashed::new(1), 1);
+        memo.advance_frame();
+        memo.get(&Hashed::new(1));
+        memo.advance_frame();
+        assert!(!memo.contains(&Hashed::new(0)));
+        assert!(memo.contains(&Hashed::new(1)));
+        memo.advance_frame();
+        assert!(memo.is_empty());
+        assert_eq!(memo.stats().expirations, 2);
+        memo.insert(Hashed::new(2), 2);
+        assert_eq!(memo.get(&Hashed::new(2)), Some(&2));
+    }
+}
@@ -0,0 +1,192 @@
+use core::hash::{BuildHasher, Hash, Hasher};
+
+/// A [`Hasher`] whose output only depends on the hashed values, and not on the platform, CPU features
+/// or the version of this crate, so that hashes can be persisted, e.g. as on-disk cache keys,
+/// or compared between machines, e.g. to detect desyncs.
+///
+/// The algorithm is frozen: SipHash-1-3 with both keys zero, over the bytes written by the [`Hash`]
+/// implementations. Integers are written little-endian, and `usize` and `isize` as 64-bit integers.
+/// Hashes still change if a [`Hash`] implementation changes what it writes, which the standard library
+/// has not done for its types so far.
+///
+/// Not resistant to HashDoS, use [`FixedState`][super::FixedState] or [`HashMap`][super::HashMap] for
+/// in-memory maps.
+#[derive(Debug, Clone)]
+pub struct StableHasher {
+    v0: u64,
+    v1: u64,
+    v2: u64,
+    v3: u64,
+    /// Bytes not yet compressed, little-endian.
+    tail: u64,
+    tail_len: usize,
+    len: u64,
+}
+
+impl StableHasher {
+    pub const fn new() -> Self {
+        Self {
+            v0: 0x736f6d6570736575,
+            v1: 0x646f72616e646f6d,
+            v2: 0x6c7967656e657261,
+            v3: 0x7465646279746573,
+            tail: 0,
+            tail_len: 0,
+            len: 0,
+        }
+    }
+
+    #[inline]
+    fn round(&mut self) {
+        self.v0 = self.v0.wrapping_add(self.v1);
+        self.v1 = self.v1.rotate_left(13) ^ self.v0;
+        self.v0 = self.v0.rotate_left(32);
+        self.v2 = self.v2.wrapping_add(self.v3);
+        self.v3 = self.v3.rotate_left(16) ^ self.v2;
+        self.v0 = self.v0.wrapping_add(self.v3);
+        self.v3 = self.v3.rotate_left(21) ^ self.v0;
+        self.v2 = self.v2.wrapping_add(self.v1);
+        self.v1 = self.v1.rotate_left(17) ^ self.v2;
+        self.v2 = self.v2.rotate_left(32);
+    }
+
+    #[inline]
+    fn compress(&mut self, word: u64) {
+        self.v3 ^= word;
+        self.round();
+        self.v0 ^= word;
+    }
+}
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reads up to 8 bytes as a little-endian integer.
+#[inline]
+fn read_le(bytes: &[u8]) -> u64 {
+    bytes.iter().rev().fold(0, |word, byte| word << 8 | *byte as u64)
+}
+
+impl Hasher for StableHasher {
+    fn write(&mut self, mut bytes: &[u8]) {
+        self.len = self.len.wrapping_add(bytes.len() as u64);
+        if self.tail_len > 0 {
+            let fill = (8 - self.tail_len).min(bytes.len());
+            self.tail |= read_le(&bytes[..fill]) << (8 * self.tail_len);
+            self.tail_len += fill;
+            bytes = &bytes[fill..];
+            if self.tail_len < 8 {
+                return;
+            }
+            self.compress(self.tail);
+            self.tail = 0;
+            self.tail_len = 0;
+        }
+        let mut words = bytes.chunks_exact(8);
+        for word in &mut words {
+            self.compress(read_le(word));
+        }
+        self.tail = read_le(words.remainder());
+        self.tail_len = words.remainder().len();
+    }
+
+    #[inline]
+    fn write_u8(&mut self, i: u8) {
+        self.write(&[i]);
+    }
+
+    #[inline]
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    #[inline]
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    #[inline]
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    #[inline]
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes());
+    }
+
+    #[inline]
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    #[inline]
+    fn write_isize(&mut self, i: isize) {
+        self.write_u64(i as i64 as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        let mut state = self.clone();
+        let last = (self.len & 0xff) << 56 | self.tail;
+        state.compress(last);
+        state.v2 ^= 0xff;
+        state.round();
+        state.round();
+        state.round();
+        state.v0 ^ state.v1 ^ state.v2 ^ state.v3
+    }
+}
+
+/// A [`BuildHasher`] creating [`StableHasher`]s, for maps whose hashes are persisted.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StableState;
+
+impl BuildHasher for StableState {
+    type Hasher = StableHasher;
+
+    #[inline]
+    fn build_hasher(&self) -> StableHasher {
+        StableHasher::new()
+    }
+}
+
+/// Hashes the value with [`StableHasher`], see there for what the hash depends on.
+pub fn stable_hash<T: ?Sized + Hash>(value: &T) -> u64 {
+    StableState.hash_one(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// These values must never change, persisted hashes depend on them.
+    #[test]
+    fn golden_values() {
+        assert_eq!(StableHasher::new().finish(), 0xd1fba762150c532c);
+        assert_eq!(stable_hash(&0u32), 0xcc2247b79ac48af0);
+        assert_eq!(stable_hash("hello"), 0xe037876b880b8ed9);
+        assert_eq!(stable_hash(&(1u8, -1i64, 'x', "tuple")), 0xa3ddbc37c7eb8f83);
+        assert_eq!(stable_hash(&vec![1u16, 2, 3]), 0xe02e5ff731c81c45);
+        assert_eq!(stable_hash(&[usize::MAX, 0]), 0x3803fb9b1e1803c4);
+        assert_eq!(stable_hash(&-2isize), stable_hash(&-2i64));
+        assert_eq!(stable_hash(&(u128::MAX - 1, Some(true), [0.5f32.to_bits(); 3])), 0xc51c7055099a2336);
+    }
+
+    #[test]
+    fn split_writes() {
+        let bytes: Vec<u8> = (0..64).collect();
+        for len in 0..bytes.len() {
+            let mut whole = StableHasher::new();
+            whole.write(&bytes[..len]);
+            for split in 0..len {
+                let mut parts = StableHasher::new();
+                parts.write(&bytes[..split]);
+                parts.write(&bytes[split..len]);
+                assert_eq!(parts.finish(), whole.finish());
+            }
+        }
+    }
+}
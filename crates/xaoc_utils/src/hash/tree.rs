@@ -0,0 +1,229 @@
+use super::fixed_hash;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::hash::Hash;
+
+/// Hashes of the chunks of a large collection, combined pairwise up to a root hash like a Merkle tree,
+/// to check cheaply whether anything changed and to find which chunks did.
+///
+/// Updating a chunk recomputes only the hashes above it. Trees with the same chunk hashes have the same
+/// [`root`][HashTree::root], however they were built, and [`diff`][HashTree::diff] only descends into the
+/// subtrees whose hashes differ. Hashes come from [`fixed_hash`], so trees can be compared between processes
+/// of the same build, e.g. to sync only the changed chunks.
+///
+/// ```
+/// use xaoc_utils::hash::HashTree;
+///
+/// let mut positions: Vec<[i32; 2]> = (0..1000).map(|i| [i, -i]).collect();
+/// let before = HashTree::from_chunks(positions.chunks(64));
+///
+/// let mut after = before.clone();
+/// positions[130] = [0, 0];
+/// after.set_chunk(130 / 64, &positions[128..192]);
+/// assert_ne!(after.root(), before.root());
+/// assert_eq!(after.diff(&before), vec![2]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashTree {
+    /// The nodes of a complete binary tree, the root at 1 and the children of `n` at `2n` and `2n + 1`,
+    /// with the chunks at the leaves from `capacity` on and the leaves past `len` zero.
+    nodes: Vec<u64>,
+    len: usize,
+}
+
+#[inline]
+fn combine(left: u64, right: u64) -> u64 {
+    fixed_hash(&(left, right))
+}
+
+impl HashTree {
+    pub fn new() -> Self {
+        Self::from_hashes([])
+    }
+
+    /// Creates a tree from the hashes of the chunks.
+    pub fn from_hashes<I: IntoIterator<Item = u64>>(hashes: I) -> Self {
+        let mut tree = Self { nodes: Vec::new(), len: 0 };
+        tree.rebuild(hashes.into_iter().collect());
+        tree
+    }
+
+    /// Creates a tree hashing each chunk with [`fixed_hash`].
+    pub fn from_chunks<T: Hash, I: IntoIterator<Item = T>>(chunks: I) -> Self {
+        Self::from_hashes(chunks.into_iter().map(|chunk| fixed_hash(&chunk)))
+    }
+
+    /// The number of chunks.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The hash of all chunks and of their number.
+    pub fn root(&self) -> u64 {
+        fixed_hash(&(self.nodes[1], self.len))
+    }
+
+    /// The hash of the chunk at `index`.
+    pub fn chunk_hash(&self, index: usize) -> Option<u64> {
+        (index < self.len).then(|| self.nodes[self.capacity() + index])
+    }
+
+    /// Sets the hash of the chunk at `index`, panicking if it is out of bounds.
+    pub fn set(&mut self, index: usize, hash: u64) {
+        assert!(index < self.len, "chunk index {index} out of bounds for a hash tree of {} chunks", self.len);
+        self.set_leaf(index, hash);
+    }
+
+    /// Sets the hash of the chunk at `index` to the [`fixed_hash`] of `chunk`, panicking if it is out of bounds.
+    pub fn set_chunk<T: ?Sized + Hash>(&mut self, index: usize, chunk: &T) {
+        self.set(index, fixed_hash(chunk));
+    }
+
+    /// Appends the hash of a chunk.
+    pub fn push(&mut self, hash: u64) {
+        if self.len == self.capacity() {
+            let mut leaves = self.leaves().to_vec();
+            leaves.push(hash);
+            self.rebuild(leaves);
+        } else {
+            self.set_leaf(self.len, hash);
+            self.len += 1;
+        }
+    }
+
+    /// Appends the [`fixed_hash`] of a chunk.
+    pub fn push_chunk<T: ?Sized + Hash>(&mut self, chunk: &T) {
+        self.push(fixed_hash(chunk));
+    }
+
+    /// Removes the chunks from `len` on.
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+        if len.next_power_of_two() < self.capacity() {
+            self.rebuild(self.leaves()[..len].to_vec());
+            return;
+        }
+        for index in len..self.len {
+            self.set_leaf(index, 0);
+        }
+        self.len = len;
+    }
+
+    /// Returns the indices of the chunks that differ between the trees, in order, including the chunks
+    /// only one of them has.
+    pub fn diff(&self, other: &HashTree) -> Vec<usize> {
+        let mut changed = Vec::new();
+        let (shared, len) = (self.len.min(other.len), self.len.max(other.len));
+        if self.capacity() == other.capacity() {
+            // Padding leaves are zero, so they can match real chunks hashed to zero in the longer tree.
+            self.diff_node(other, 1, &mut changed);
+            changed.retain(|&index| index < shared);
+        } else {
+            changed.extend((0..shared).filter(|&index| self.chunk_hash(index) != other.chunk_hash(index)));
+        }
+        changed.extend(shared..len);
+        changed
+    }
+
+    fn diff_node(&self, other: &HashTree, node: usize, changed: &mut Vec<usize>) {
+        if self.nodes[node] == other.nodes[node] {
+            return;
+        }
+        let capacity = self.capacity();
+        if node >= capacity {
+            changed.push(node - capacity);
+        } else {
+            self.diff_node(other, 2 * node, changed);
+            self.diff_node(other, 2 * node + 1, changed);
+        }
+    }
+
+    /// The number of leaves, the smallest power of two not below `len`.
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.nodes.len() / 2
+    }
+
+    fn leaves(&self) -> &[u64] {
+        &self.nodes[self.capacity()..][..self.len]
+    }
+
+    fn set_leaf(&mut self, index: usize, hash: u64) {
+        let mut node = self.capacity() + index;
+        self.nodes[node] = hash;
+        while node > 1 {
+            node /= 2;
+            self.nodes[node] = combine(self.nodes[2 * node], self.nodes[2 * node + 1]);
+        }
+    }
+
+    fn rebuild(&mut self, leaves: Vec<u64>) {
+        let capacity = leaves.len().next_power_of_two();
+        self.nodes = vec![0; 2 * capacity];
+        self.nodes[capacity..][..leaves.len()].copy_from_slice(&leaves);
+        for node in (1..capacity).rev() {
+            self.nodes[node] = combine(self.nodes[2 * node], self.nodes[2 * node + 1]);
+        }
+        self.len = leaves.len();
+    }
+}
+
+impl Default for HashTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn incremental() {
+        let chunks: Vec<u32> = (0..5).collect();
+        let mut tree = HashTree::new();
+        for chunk in &chunks {
+            tree.push_chunk(chunk);
+        }
+        assert_eq!(tree, HashTree::from_chunks(&chunks));
+        assert_eq!(tree.chunk_hash(4), Some(fixed_hash(&4u32)));
+        assert_eq!(tree.chunk_hash(5), None);
+
+        let root = tree.root();
+        tree.set_chunk(3, &7u32);
+        assert_ne!(tree.root(), root);
+        tree.set_chunk(3, &3u32);
+        assert_eq!(tree.root(), root);
+
+        tree.push(0);
+        tree.truncate(5);
+        assert_eq!(tree.root(), root);
+        tree.truncate(3);
+        assert_eq!(tree, HashTree::from_chunks(&chunks[..3]));
+        assert_ne!(HashTree::from_hashes([0]).root(), HashTree::from_hashes([0, 0]).root());
+    }
+
+    #[test]
+    fn diff() {
+        let before = HashTree::from_chunks(0..100u32);
+        let mut after = before.clone();
+        assert!(after.diff(&before).is_empty());
+        after.set_chunk(99, &0u32);
+        after.set_chunk(7, &0u32);
+        assert_eq!(after.diff(&before), vec![7, 99]);
+
+        after.truncate(98);
+        assert_eq!(after.diff(&before), vec![7, 98, 99]);
+        after.truncate(50);
+        assert_eq!(after.diff(&before), [7].into_iter().chain(50..100).collect::<Vec<_>>());
+        assert_eq!(HashTree::new().diff(&HashTree::from_hashes([1])), vec![0]);
+        assert_eq!(HashTree::from_hashes([1, 2, 3]).diff(&HashTree::from_hashes([1, 2, 3, 0])), vec![3]);
+        assert_eq!(HashTree::from_hashes([1, 2, 3, 0]).diff(&HashTree::from_hashes([1, 2, 3])), vec![3]);
+    }
+}
@@ -0,0 +1,154 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Identifies a type across builds, unlike [`TypeId`][core::any::TypeId], which may change with every
+/// compilation and differs between the host binary and separately built plugins.
+///
+/// Derive it, or implement it with [`stable_type_id!`][crate::stable_type_id], either from a declared UUID,
+/// which keeps the id when the type is moved or renamed, or from the fully qualified path of the type.
+/// Generic types combine the ids of their parameters with [`combine_type_ids`].
+pub trait StableTypeId: 'static {
+    const STABLE_TYPE_ID: u128;
+}
+
+/// Implements [`StableTypeId`] for a type, from the fully qualified path of the type or from a declared UUID.
+///
+/// ```
+/// use xaoc_utils::hash::{parse_uuid, StableTypeId};
+/// use xaoc_utils::stable_type_id;
+///
+/// struct Player;
+/// struct Transform;
+///
+/// stable_type_id!(Player);
+/// stable_type_id!(Transform = "67e55044-10b1-426f-9247-bb680e5fe0c8");
+///
+/// assert_eq!(Transform::STABLE_TYPE_ID, parse_uuid("67e55044-10b1-426f-9247-bb680e5fe0c8"));
+/// assert_ne!(Player::STABLE_TYPE_ID, Transform::STABLE_TYPE_ID);
+/// ```
+#[macro_export]
+macro_rules! stable_type_id {
+    ($ty:ident) => {
+        impl $crate::hash::StableTypeId for $ty {
+            const STABLE_TYPE_ID: u128 = $crate::hash::type_path_id(concat!(module_path!(), "::", stringify!($ty)));
+        }
+    };
+    ($ty:ty = $uuid:literal) => {
+        impl $crate::hash::StableTypeId for $ty {
+            const STABLE_TYPE_ID: u128 = $crate::hash::parse_uuid($uuid);
+        }
+    };
+}
+
+/// Hashes a fully qualified type path into an id, at compile time, with FNV-1a 128.
+pub const fn type_path_id(path: &str) -> u128 {
+    let bytes = path.as_bytes();
+    let mut id: u128 = 0x6c62272e07bb014262b821756295c58d;
+    let mut i = 0;
+    while i < bytes.len() {
+        id ^= bytes[i] as u128;
+        id = id.wrapping_mul(0x1000000000000000000013b);
+        i += 1;
+    }
+    id
+}
+
+/// Parses a UUID such as `"67e55044-10b1-426f-9247-bb680e5fe0c8"` at compile time, panicking if it is malformed.
+pub const fn parse_uuid(uuid: &str) -> u128 {
+    let bytes = uuid.as_bytes();
+    assert!(bytes.len() == 36, "a UUID must have 36 characters");
+    let mut id: u128 = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if i == 8 || i == 13 || i == 18 || i == 23 {
+            assert!(bytes[i] == b'-', "a UUID must be 32 hexadecimal digits in groups of 8-4-4-4-12");
+            i += 1;
+            continue;
+        }
+        let digit = match bytes[i] {
+            b @ b'0'..=b'9' => b - b'0',
+            b @ b'a'..=b'f' => b - b'a' + 10,
+            b @ b'A'..=b'F' => b - b'A' + 10,
+            _ => panic!("a UUID must be 32 hexadecimal digits in groups of 8-4-4-4-12"),
+        };
+        id = id << 4 | digit as u128;
+        i += 1;
+    }
+    id
+}
+
+/// Combines the id of a generic type with the ids of its type parameters, in order.
+pub const fn combine_type_ids(base: u128, parameters: &[u128]) -> u128 {
+    let mut id = base;
+    let mut i = 0;
+    while i < parameters.len() {
+        id = (id ^ parameters[i]).wrapping_mul(0x1000000000000000000013b).rotate_left(47);
+        i += 1;
+    }
+    id
+}
+
+macro_rules! impl_stable_type_id {
+    ($($ty:ty),*) => {
+        $(
+            impl StableTypeId for $ty {
+                const STABLE_TYPE_ID: u128 = type_path_id(stringify!($ty));
+            }
+        )*
+    };
+}
+
+impl_stable_type_id!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, bool, char, str, ());
+
+impl StableTypeId for String {
+    const STABLE_TYPE_ID: u128 = type_path_id("alloc::string::String");
+}
+
+impl<T: StableTypeId> StableTypeId for Vec<T> {
+    const STABLE_TYPE_ID: u128 = combine_type_ids(type_path_id("alloc::vec::Vec"), &[T::STABLE_TYPE_ID]);
+}
+
+impl<T: StableTypeId> StableTypeId for Option<T> {
+    const STABLE_TYPE_ID: u128 = combine_type_ids(type_path_id("core::option::Option"), &[T::STABLE_TYPE_ID]);
+}
+
+impl<T: StableTypeId> StableTypeId for [T] {
+    const STABLE_TYPE_ID: u128 = combine_type_ids(type_path_id("[]"), &[T::STABLE_TYPE_ID]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct ByPath;
+    crate::stable_type_id!(ByPath);
+
+    #[derive(crate::hash::StableTypeId)]
+    struct Derived;
+
+    #[derive(crate::hash::StableTypeId)]
+    #[stable_type_id(uuid = "67e55044-10b1-426f-9247-bb680e5fe0c8")]
+    struct DerivedGeneric<T, U>(T, U);
+
+    #[test]
+    fn stable_type_ids() {
+        assert_eq!(u32::STABLE_TYPE_ID, const_fnv1a_hash::fnv1a_hash_str_128("u32"));
+        assert_eq!(ByPath::STABLE_TYPE_ID, type_path_id("xaoc_utils::hash::type_id::tests::ByPath"));
+        assert_eq!(parse_uuid("67e55044-10b1-426F-9247-BB680E5FE0C8"), 0x67e5504410b1426f9247bb680e5fe0c8);
+        assert_ne!(Vec::<u32>::STABLE_TYPE_ID, Vec::<i32>::STABLE_TYPE_ID);
+        assert_ne!(Option::<Vec<u8>>::STABLE_TYPE_ID, Vec::<Option<u8>>::STABLE_TYPE_ID);
+        assert_ne!(combine_type_ids(1, &[2, 3]), combine_type_ids(1, &[3, 2]));
+
+        assert_eq!(Derived::STABLE_TYPE_ID, type_path_id("xaoc_utils::hash::type_id::tests::Derived"));
+        assert_eq!(
+            DerivedGeneric::<u32, Derived>::STABLE_TYPE_ID,
+            combine_type_ids(0x67e5504410b1426f9247bb680e5fe0c8, &[u32::STABLE_TYPE_ID, Derived::STABLE_TYPE_ID])
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn malformed_uuid() {
+        parse_uuid("67e55044-10b1-426f-9247_bb680e5fe0c8");
+    }
+}
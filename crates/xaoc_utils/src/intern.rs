@@ -0,0 +1,208 @@
+use crate::hash::{fixed_hash, Hashed, PassHash};
+use crate::sync::RwLock;
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::fmt::{Debug, Display, Formatter};
+use core::hash::{Hash, Hasher};
+use core::ops::Deref;
+use hashbrown::hash_map::RawEntryMut;
+
+/// Deduplicates immutable values, so that equal values share one allocation and compare by pointer,
+/// like labels do with their names. Interned values are leaked and live until the end of the program.
+///
+/// ```
+/// use xaoc_utils::intern::Interner;
+///
+/// #[derive(Debug, Hash, PartialEq, Eq)]
+/// struct MeshLayout {
+///     attributes: Vec<&'static str>,
+/// }
+///
+/// static LAYOUTS: Interner<MeshLayout> = Interner::new();
+///
+/// let a = LAYOUTS.intern(MeshLayout { attributes: vec!["position", "normal"] });
+/// let b = LAYOUTS.intern(MeshLayout { attributes: vec!["position", "normal"] });
+/// assert_eq!(a, b);
+/// assert!(std::ptr::eq(&*a, &*b));
+/// ```
+pub struct Interner<T: 'static> {
+    /// Keyed by the leaked values, which hash to their pre-computed hash, unlike [`Interned`].
+    values: RwLock<hashbrown::HashMap<&'static Hashed<T>, (), PassHash>>,
+}
+
+impl<T: 'static> Interner<T> {
+    pub const fn new() -> Self {
+        Self { values: RwLock::new(hashbrown::HashMap::with_hasher(PassHash)) }
+    }
+
+    /// The number of distinct values interned.
+    pub fn len(&self) -> usize {
+        self.values.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.read().is_empty()
+    }
+
+    /// Returns every interned value, in no particular order.
+    pub fn interned(&self) -> Vec<Interned<T>> {
+        self.values.read().keys().map(|value| Interned(value)).collect()
+    }
+}
+
+impl<T: Hash + Eq + 'static> Interner<T> {
+    /// Returns the handle of the value, interning it if no equal value was before.
+    pub fn intern(&self, value: T) -> Interned<T> {
+        self.intern_hashed(Hashed::new(value))
+    }
+
+    /// Like [`Interner::intern`], but with a value that is already hashed.
+    pub fn intern_hashed(&self, value: Hashed<T>) -> Interned<T> {
+        if let Some(interned) = self.get_hashed(value.hash(), &*value) {
+            return interned;
+        }
+        let mut values = self.values.write();
+        match values.raw_entry_mut().from_hash(value.hash(), |interned| interned.is(value.hash(), &*value)) {
+            RawEntryMut::Occupied(entry) => Interned(entry.key()),
+            RawEntryMut::Vacant(entry) => {
+                let hash = value.hash();
+                let (value, _) = entry.insert_hashed_nocheck(hash, Box::leak(Box::new(value)), ());
+                Interned(value)
+            }
+        }
+    }
+
+    /// Like [`Interner::intern`], but only converts the borrowed value into an owned one,
+    /// e.g. a `&str` into a `String`, if no equal value was interned before.
+    pub fn intern_borrowed<Q: ?Sized + Hash + Eq + ToOwned<Owned = T>>(&self, value: &Q) -> Interned<T>
+    where
+        T: Borrow<Q>,
+    {
+        match self.get(value) {
+            Some(interned) => interned,
+            None => self.intern(value.to_owned()),
+        }
+    }
+
+    /// Returns the handle of the value if it was interned.
+    pub fn get<Q: ?Sized + Hash + Eq>(&self, value: &Q) -> Option<Interned<T>>
+    where
+        T: Borrow<Q>,
+    {
+        self.get_hashed(fixed_hash(value), value)
+    }
+
+    fn get_hashed<Q: ?Sized + Eq>(&self, hash: u64, value: &Q) -> Option<Interned<T>>
+    where
+        T: Borrow<Q>,
+    {
+        self.values
+            .read()
+            .raw_entry()
+            .from_hash(hash, |interned| interned.is(hash, value))
+            .map(|(interned, _)| Interned(interned))
+    }
+}
+
+impl<T: 'static> Default for Interner<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A handle to a value deduplicated by an [`Interner`], which compares and hashes by pointer.
+pub struct Interned<T: 'static>(&'static Hashed<T>);
+
+impl<T> Interned<T> {
+    /// The interned value with its pre-computed hash.
+    #[inline]
+    pub fn hashed(&self) -> &'static Hashed<T> {
+        self.0
+    }
+
+    #[inline]
+    pub fn as_ptr(&self) -> *const T {
+        &**self.0
+    }
+}
+
+impl<T> Deref for Interned<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        self.0
+    }
+}
+
+impl<T> Clone for Interned<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T> Copy for Interned<T> {}
+
+impl<T> PartialEq for Interned<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::eq(self.0, other.0)
+    }
+}
+impl<T> Eq for Interned<T> {}
+impl<T> Hash for Interned<T> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        core::ptr::hash(self.0, state)
+    }
+}
+
+impl<T: Debug> Debug for Interned<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Debug::fmt(&**self.0, f)
+    }
+}
+
+impl<T: Display> Display for Interned<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(&**self.0, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::HashSet;
+
+    #[test]
+    fn intern() {
+        let interner = Interner::<String>::new();
+        let a = interner.intern("a".to_string());
+        assert_eq!(interner.intern_borrowed("a"), a);
+        assert_eq!(interner.get("a"), Some(a));
+        assert_eq!(interner.get("b"), None);
+        let b = interner.intern_borrowed("b");
+        assert_ne!(a, b);
+        assert_eq!((a.as_str(), b.to_string()), ("a", "b".to_string()));
+        assert_eq!(interner.len(), 2);
+
+        let set: HashSet<_> = [a, b, interner.intern("a".to_string())].into_iter().collect();
+        assert_eq!(set.len(), 2);
+        assert_eq!(a.hashed().hash(), fixed_hash("a"));
+    }
+
+    #[test]
+    fn threads() {
+        static INTERNER: Interner<Vec<u32>> = Interner::new();
+        let handles: Vec<_> = std::thread::scope(|scope| {
+            let threads: Vec<_> = (0..8)
+                .map(|_| scope.spawn(|| (0..100).map(|i| INTERNER.intern(vec![i])).collect::<Vec<_>>()))
+                .collect();
+            threads.into_iter().map(|thread| thread.join().unwrap()).collect()
+        });
+        assert!(handles.iter().all(|handle| *handle == handles[0]));
+        assert_eq!(INTERNER.len(), 100);
+        assert_eq!(INTERNER.get(&[5][..]), Some(handles[0][5]));
+    }
+}
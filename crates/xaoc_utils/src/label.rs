@@ -1,43 +1,128 @@
-use std::any::TypeId;
-use std::borrow::Cow;
-use std::fmt::{Debug, Formatter};
-use std::hash::{Hash, Hasher};
-use std::marker::PhantomData;
-
-mod hashes_table {
-    use crate::hash::HashMap;
-    use hashbrown::hash_map::Entry;
-    use parking_lot::Mutex;
-    use std::any::TypeId;
-
-    const HASHES: once_cell::sync::Lazy<Mutex<HashMap<(TypeId, u64), &'static str>>> =
-        once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
-
-    pub fn intern(type_id: TypeId, id: u64, name: &'static str) -> &'static str {
-        let hashes = &*HASHES;
-        let mut _lock = hashes.lock();
-        match _lock.entry((type_id, id)) {
-            Entry::Occupied(o) => {
-                if *o.get() != name {
-                    panic!("Duplicate hash value {:08x} for strings {:?} and {:?}", id, name, o.get())
-                } else {
-                    *o.get()
-                }
-            }
-            Entry::Vacant(v) => v.insert(name),
-        }
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{Debug, Display, Formatter};
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+
+mod alias;
+mod declared;
+mod dynamic;
+mod macros;
+mod path;
+mod registry;
+#[cfg(feature = "serde")]
+mod serde;
+mod set;
+
+pub use alias::{set_label_alias_hook, LabelAlias, LabelAliasError, LabelAliasHook};
+pub use declared::{declared_labels, register_declared_labels, LabelConflict, LabelDeclaration, LabelReport};
+pub use dynamic::DynLabel;
+#[doc(hidden)]
+pub use macros::{count_same_id, count_same_name};
+pub use registry::{install_label_registry, DynLabelStats, InstallRegistryError, LabelRegistry};
+pub use set::{LabelHashMap, LabelHashSet, LabelMap, LabelSet};
+
+#[doc(hidden)]
+pub mod __private {
+    pub use inventory;
+}
+
+/// Separates the segments of hierarchical label names, see [`Label::parent`] and [`Label::child`].
+pub const SEPARATOR: char = '/';
+
+/// The id of a label, a FNV-1a hash of its name, 64-bit wide or 128-bit wide with the `wide_label_ids` feature.
+#[cfg(not(feature = "wide_label_ids"))]
+pub type LabelId = u64;
+/// The id of a label, a FNV-1a hash of its name, 64-bit wide or 128-bit wide with the `wide_label_ids` feature.
+#[cfg(feature = "wide_label_ids")]
+pub type LabelId = u128;
+
+#[cfg(not(feature = "wide_label_ids"))]
+const FNV_OFFSET_BASIS: LabelId = 0xcbf29ce484222325;
+#[cfg(not(feature = "wide_label_ids"))]
+const FNV_PRIME: LabelId = 0x100000001b3;
+#[cfg(feature = "wide_label_ids")]
+const FNV_OFFSET_BASIS: LabelId = 0x6c62272e07bb014262b821756295c58d;
+#[cfg(feature = "wide_label_ids")]
+const FNV_PRIME: LabelId = 0x1000000000000000000013b;
+
+/// A type that labels are grouped by. Labels of a domain are registered, compared and enumerated
+/// separately from the labels of other domains.
+/// Salting the ids with [`LabelDomain::SALT`] is optional, without it the ids are unsalted.
+/// ```
+/// use xaoc_utils::label::{ConstLabel, LabelDomain};
+///
+/// struct AssetDomain;
+///
+/// impl LabelDomain for AssetDomain {
+///     const NAME: &'static str = "my_game::assets";
+///     const SALT: &'static str = "asset";
+/// }
+///
+/// const GRASS: ConstLabel<AssetDomain> = ConstLabel::new("textures/grass");
+/// ```
+pub trait LabelDomain: 'static {
+    /// Identifies the domain in the registry shared with plugins, see [`LabelRegistry`], as its `TypeId` differs
+    /// in each separately built plugin. Must be unique within the program, prefixing it with the crate name
+    /// avoids clashes: registering labels of a domain whose name another domain registered first panics.
+    const NAME: &'static str;
+
+    /// Mixed into the ids of the domain's labels when not empty, so that the same name gets
+    /// a different id in each salted domain, e.g. when ids of several domains share a table.
+    /// Changing the salt changes every id of the domain, so persisted ids must be migrated.
+    const SALT: &'static str = "";
+}
+
+/// The id every label id of the domain continues from, the hash of the salt.
+trait DomainSeed {
+    const SEED: LabelId;
+}
+
+impl<Domain: LabelDomain> DomainSeed for Domain {
+    const SEED: LabelId = if Domain::SALT.is_empty() {
+        FNV_OFFSET_BASIS
+    } else {
+        // The separator keeps e.g. salt "a" + name "bc" apart from salt "ab" + name "c".
+        continue_id(continue_id(FNV_OFFSET_BASIS, Domain::SALT.as_bytes()), &[0xff])
+    };
+}
+
+/// Computes the id of a label name in the domain.
+const fn label_id<Domain: LabelDomain>(name: &str) -> LabelId {
+    continue_id(<Domain as DomainSeed>::SEED, name.as_bytes())
+}
+
+/// Feeds more bytes into a label id, so that the id of `"a/b"` can be computed from the id of `"a"`.
+const fn continue_id(mut id: LabelId, bytes: &[u8]) -> LabelId {
+    let mut i = 0;
+    while i < bytes.len() {
+        id ^= bytes[i] as LabelId;
+        id = id.wrapping_mul(FNV_PRIME);
+        i += 1;
     }
+    id
 }
 
 pub struct ConstLabel<Domain> {
-    id: u64,
+    id: LabelId,
     name: &'static str,
     _marker: PhantomData<Domain>,
 }
 
-impl<Domain: 'static> ConstLabel<Domain> {
+impl<Domain: LabelDomain> ConstLabel<Domain> {
     pub const fn new(name: &'static str) -> Self {
-        Self { id: const_fnv1a_hash::fnv1a_hash_str_64(name), name, _marker: PhantomData }
+        Self { id: label_id::<Domain>(name), name, _marker: PhantomData }
+    }
+
+    #[inline]
+    pub const fn id(&self) -> LabelId {
+        self.id
+    }
+
+    #[inline]
+    pub const fn name(&self) -> &'static str {
+        self.name
     }
 
     pub fn label(self) -> Label<Domain> {
@@ -45,55 +130,134 @@ impl<Domain: 'static> ConstLabel<Domain> {
     }
 }
 
+impl<Domain> Clone for ConstLabel<Domain> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<Domain> Copy for ConstLabel<Domain> {}
+
+impl<Domain> Debug for ConstLabel<Domain> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "ConstLabel<{}>({:?}, 0x{:08x})", core::any::type_name::<Domain>(), self.name, self.id)
+    }
+}
+
 pub struct Label<Domain> {
-    id: u64,
+    id: LabelId,
+    index: u32,
     name: &'static str,
     _marker: PhantomData<Domain>,
 }
 
-impl<Domain: 'static> From<ConstLabel<Domain>> for Label<Domain> {
+impl<Domain: LabelDomain> From<ConstLabel<Domain>> for Label<Domain> {
     fn from(from: ConstLabel<Domain>) -> Self {
-        #[cfg(feature = "check_label_hashes")]
-        hashes_table::intern(TypeId::of::<Domain>(), from.id, from.name);
-        Self { id: from.id, name: from.name, _marker: PhantomData }
+        match registry::intern(registry::domain::<Domain>(), from.id, from.name) {
+            Ok(record) => record.into(),
+            Err(existing) => panic!("{}", LabelCollision { id: from.id, name: from.name.into(), existing }),
+        }
+    }
+}
+
+/// The error returned when a label name hashes to the id of a different, already registered name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabelCollision {
+    pub id: LabelId,
+    pub name: String,
+    pub existing: Cow<'static, str>,
+}
+
+impl Display for LabelCollision {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Duplicate hash value {:08x} for strings {:?} and {:?}", self.id, self.name, self.existing)
     }
 }
 
+impl core::error::Error for LabelCollision {}
+
 impl<Domain> Debug for Label<Domain> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Label<{}>({:?}, 0x{:08x})", std::any::type_name::<Domain>(), self.name, self.id)
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Label<{}>({:?}, 0x{:08x})", core::any::type_name::<Domain>(), self.name, self.id)
     }
 }
 
-impl<Domain: 'static> Label<Domain> {
+impl<Domain: LabelDomain> Label<Domain> {
+    /// Registers the name in this domain. Owned names are leaked,
+    /// use [`DynLabel`] for names that should be reclaimed once unused.
     pub fn new<S: Into<Cow<'static, str>>>(name: S) -> Self {
+        Self::try_new(name).unwrap_or_else(|collision| panic!("{}", collision))
+    }
+
+    /// Like [`Label::new`], but returns an error instead of panicking if the name collides
+    /// with a different name already registered in this domain.
+    pub fn try_new<S: Into<Cow<'static, str>>>(name: S) -> Result<Self, LabelCollision> {
         let name = name.into();
-        let id = const_fnv1a_hash::fnv1a_hash_str_64(name.as_ref());
-        let name = match name {
-            Cow::Borrowed(name) => {
-                #[cfg(feature = "check_label_hashes")]
-                hashes_table::intern(TypeId::of::<Domain>(), id, name);
-                name
-            }
-            Cow::Owned(name) => hashes_table::intern(TypeId::of::<Domain>(), id, Box::leak(name.into_boxed_str())),
+        let id = label_id::<Domain>(name.as_ref());
+        let interned = match &name {
+            Cow::Borrowed(name) => registry::intern(registry::domain::<Domain>(), id, name),
+            Cow::Owned(name) => registry::intern_owned(registry::domain::<Domain>(), id, name),
         };
-        Self { id, name, _marker: PhantomData }
+        match interned {
+            Ok(record) => Ok(record.into()),
+            Err(existing) => Err(LabelCollision { id, name: name.into_owned(), existing }),
+        }
+    }
+
+    /// Returns the label registered in this domain under the given id, if any.
+    pub fn from_id(id: LabelId) -> Option<Self> {
+        registry::get(registry::domain::<Domain>(), id, None).map(Self::from)
+    }
+
+    /// Returns the label registered in this domain under the given dense index, if any.
+    pub fn from_index(index: u32) -> Option<Self> {
+        registry::get_by_index(registry::domain::<Domain>(), index).map(Self::from)
+    }
+
+    /// Returns the label registered in this domain under the given name, if any.
+    /// Unlike [`Label::new`] this never registers a new label.
+    pub fn lookup(name: &str) -> Option<Self> {
+        let id = label_id::<Domain>(name);
+        registry::get(registry::domain::<Domain>(), id, Some(name)).map(Self::from)
+    }
+
+    /// Returns every label registered in this domain, in registration order.
+    pub fn registered() -> Vec<Self> {
+        registry::entries(registry::domain::<Domain>()).into_iter().map(Self::from).collect()
     }
 
     #[inline]
-    pub fn id(&self) -> u64 {
+    pub fn id(&self) -> LabelId {
         self.id
     }
 
+    /// A small index assigned by the registry in registration order, unique within the domain.
+    /// Used by [`LabelSet`] and [`LabelMap`] for lookups without hashing.
+    #[inline]
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
     #[inline]
     pub fn name(&self) -> &str {
         self.name
     }
 }
 
+impl<Domain> From<registry::Record> for Label<Domain> {
+    fn from(record: registry::Record) -> Self {
+        Self { id: record.id, index: record.index, name: record.name, _marker: PhantomData }
+    }
+}
+
+impl<Domain> From<Label<Domain>> for registry::Record {
+    fn from(label: Label<Domain>) -> Self {
+        Self { id: label.id, index: label.index, name: label.name }
+    }
+}
+
 impl<Domain> Clone for Label<Domain> {
     fn clone(&self) -> Self {
-        Self { id: self.id, name: self.name, _marker: PhantomData }
+        *self
     }
 }
 impl<Domain> Copy for Label<Domain> {}
@@ -115,6 +279,9 @@ mod tests {
     use super::*;
 
     struct Tag;
+    impl LabelDomain for Tag {
+        const NAME: &'static str = "xaoc_utils::label::tests::Tag";
+    }
 
     #[test]
     fn const_label() {
@@ -146,4 +313,65 @@ mod tests {
         assert_eq!(l1.id(), l3.id());
         assert_eq!(l1.name(), l3.name());
     }
+
+    #[test]
+    fn continued_id() {
+        #[cfg(not(feature = "wide_label_ids"))]
+        let hash = const_fnv1a_hash::fnv1a_hash_str_64;
+        #[cfg(feature = "wide_label_ids")]
+        let hash = const_fnv1a_hash::fnv1a_hash_str_128;
+
+        assert_eq!(label_id::<Tag>("render"), hash("render"));
+        assert_eq!(continue_id(label_id::<Tag>("render"), b"/main"), hash("render/main"));
+    }
+
+    #[test]
+    fn salted_id() {
+        struct Salted;
+        impl LabelDomain for Salted {
+            const NAME: &'static str = "xaoc_utils::label::tests::salted_id::Salted";
+            const SALT: &'static str = "salted";
+        }
+        const SALTED: ConstLabel<Salted> = ConstLabel::new("render/main");
+        const PLAIN: ConstLabel<Tag> = ConstLabel::new("render/main");
+
+        assert_ne!(SALTED.id(), PLAIN.id());
+        assert_eq!(SALTED.label(), Label::<Salted>::new("render/main"));
+        assert_eq!(SALTED.label().parent().unwrap().child("main"), SALTED.label());
+        assert_eq!(Label::<Salted>::from_id(SALTED.id()).map(|label| label.name), Some("render/main"));
+    }
+
+    #[test]
+    fn registry_lookup() {
+        struct Lookup;
+        impl LabelDomain for Lookup {
+            const NAME: &'static str = "xaoc_utils::label::tests::registry_lookup::Lookup";
+        }
+        const L1: ConstLabel<Lookup> = ConstLabel::new("lookup_1");
+
+        assert_eq!(Label::<Lookup>::from_id(L1.id), None);
+        let l1 = L1.label();
+        let l2 = Label::<Lookup>::new(String::from("lookup_2"));
+        assert_eq!(Label::<Lookup>::from_id(l1.id()), Some(l1));
+        assert_eq!(Label::<Lookup>::from_id(l2.id()).map(|l| l.name), Some("lookup_2"));
+        assert_eq!(Label::<Lookup>::lookup("lookup_2"), Some(l2));
+        assert_eq!(Label::<Lookup>::lookup("lookup_3"), None);
+        assert_eq!(Label::<Tag>::from_id(l2.id()).map(|l| l.name), None::<&str>);
+        assert_eq!(Label::<Lookup>::registered(), vec![l1, l2]);
+    }
+
+    #[test]
+    fn registry_collision() {
+        struct Collision;
+        impl LabelDomain for Collision {
+            const NAME: &'static str = "xaoc_utils::label::tests::registry_collision::Collision";
+        }
+        let id = label_id::<Collision>("collision_1");
+        registry::intern(registry::domain::<Collision>(), id, "collision_2").unwrap();
+        let error = Label::<Collision>::try_new("collision_1").unwrap_err();
+        assert_eq!(error.id, id);
+        assert_eq!(error.name, "collision_1");
+        assert_eq!(error.existing, "collision_2");
+        assert_eq!(Label::<Collision>::from_id(id).unwrap().name(), "collision_2");
+    }
 }
@@ -0,0 +1,162 @@
+use super::{label_id, registry, Label, LabelCollision, LabelDomain, LabelId};
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+
+/// An old name of a label, kept so that ids and names saved before a rename still resolve.
+/// See [`Label::add_alias`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LabelAlias {
+    /// The [`LabelDomain::NAME`] of the domain.
+    pub domain: &'static str,
+    /// The id of the old name.
+    pub id: LabelId,
+    /// The old name.
+    pub name: &'static str,
+    /// The id of the label the old name resolves to.
+    pub target: LabelId,
+    /// The name of the label the old name resolves to.
+    pub target_name: &'static str,
+    target_index: u32,
+}
+
+impl LabelAlias {
+    pub(super) fn new(domain: &'static str, id: LabelId, name: &'static str, target: registry::Record) -> Self {
+        Self { domain, id, name, target: target.id, target_name: target.name, target_index: target.index }
+    }
+
+    pub(super) fn target_record(&self) -> registry::Record {
+        registry::Record { id: self.target, index: self.target_index, name: self.target_name }
+    }
+}
+
+/// The error returned when an alias can't be added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LabelAliasError {
+    /// The old name is still registered as a label.
+    Registered,
+    /// The old name is already an alias of another label.
+    Aliased { target: &'static str },
+    /// The id of the old name is already used by a different name.
+    Collision(LabelCollision),
+}
+
+impl Display for LabelAliasError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Registered => f.write_str("the aliased name is registered as a label"),
+            Self::Aliased { target } => write!(f, "the aliased name is already an alias of {:?}", target),
+            Self::Collision(collision) => Display::fmt(collision, f),
+        }
+    }
+}
+
+impl core::error::Error for LabelAliasError {}
+
+/// Renames of labels, so that data saved with an old name or id keeps working.
+impl<Domain: LabelDomain> Label<Domain> {
+    /// Declares `name` as an old name of this label. From then on, looking up the old name or its id with
+    /// [`Label::new`], [`Label::lookup`], [`Label::from_id`], their [`DynLabel`][super::DynLabel] equivalents
+    /// or through deserialization resolves to this label, and runs the hook set with [`set_label_alias_hook`].
+    ///
+    /// Fails if the old name is still registered as a label of its own, so an alias must be added before
+    /// anything uses the old name, typically at startup.
+    /// ```
+    /// use xaoc_utils::label::{Label, LabelDomain};
+    ///
+    /// struct SystemDomain;
+    /// impl LabelDomain for SystemDomain {
+    ///     const NAME: &'static str = "my_game::systems";
+    /// }
+    ///
+    /// let controller = Label::<SystemDomain>::new("player/controller");
+    /// let old = controller.add_alias("player_ctrl").unwrap();
+    ///
+    /// assert_eq!(Label::<SystemDomain>::from_id(old.id), Some(controller));
+    /// assert_eq!(Label::<SystemDomain>::new("player_ctrl"), controller);
+    /// ```
+    pub fn add_alias<S: Into<Cow<'static, str>>>(&self, name: S) -> Result<LabelAlias, LabelAliasError> {
+        let name = name.into();
+        let id = label_id::<Domain>(name.as_ref());
+        registry::add_alias(registry::domain::<Domain>(), id, &name, (*self).into())
+    }
+
+    /// Returns every alias of this domain, sorted by old name.
+    pub fn aliases() -> Vec<LabelAlias> {
+        registry::aliases(registry::domain::<Domain>())
+    }
+
+    /// Returns the old names of this label.
+    pub fn old_names(&self) -> Vec<&'static str> {
+        Self::aliases().into_iter().filter(|alias| alias.target == self.id).map(|alias| alias.name).collect()
+    }
+}
+
+/// See [`set_label_alias_hook`].
+pub type LabelAliasHook = fn(&LabelAlias);
+
+/// Sets a hook run whenever an old name or id of any domain is resolved, e.g. to warn about data that
+/// should be migrated, or `None` to remove it. The hook must not create or look up labels.
+pub fn set_label_alias_hook(hook: Option<LabelAliasHook>) {
+    registry::set_alias_hook(hook);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::label::{ConstLabel, DynLabel};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn alias() {
+        struct Tag;
+        impl LabelDomain for Tag {
+            const NAME: &'static str = "xaoc_utils::label::alias::tests::alias::Tag";
+        }
+
+        static RESOLVED: AtomicUsize = AtomicUsize::new(0);
+        set_label_alias_hook(Some(|alias| {
+            if alias.domain.ends_with("alias::Tag") {
+                assert_eq!((alias.name, alias.target_name), ("player_ctrl", "player/controller"));
+                RESOLVED.fetch_add(1, Ordering::Relaxed);
+            }
+        }));
+
+        let controller = Label::<Tag>::new("player/controller");
+        let alias = controller.add_alias("player_ctrl").unwrap();
+        assert_eq!(alias.id, ConstLabel::<Tag>::new("player_ctrl").id());
+        assert_eq!(controller.add_alias(String::from("player_ctrl")), Ok(alias));
+
+        assert_eq!(Label::<Tag>::from_id(alias.id), Some(controller));
+        assert_eq!(Label::<Tag>::lookup("player_ctrl"), Some(controller));
+        assert_eq!(Label::<Tag>::new("player_ctrl"), controller);
+        assert_eq!(Label::<Tag>::new(String::from("player_ctrl")).index(), controller.index());
+        assert_eq!(DynLabel::<Tag>::new("player_ctrl"), controller);
+        assert_eq!(DynLabel::<Tag>::from_id(alias.id), Some(controller.into()));
+        assert_eq!(RESOLVED.load(Ordering::Relaxed), 6);
+
+        assert_eq!(Label::<Tag>::registered(), [controller]);
+        assert_eq!(Label::<Tag>::aliases(), [alias]);
+        assert_eq!(controller.old_names(), ["player_ctrl"]);
+        set_label_alias_hook(None);
+    }
+
+    #[test]
+    fn invalid_alias() {
+        struct Tag;
+        impl LabelDomain for Tag {
+            const NAME: &'static str = "xaoc_utils::label::alias::tests::invalid_alias::Tag";
+        }
+
+        let a = Label::<Tag>::new("a");
+        let b = Label::<Tag>::new("b");
+        assert_eq!(a.add_alias("b"), Err(LabelAliasError::Registered));
+        b.add_alias("old").unwrap();
+        assert_eq!(a.add_alias("old"), Err(LabelAliasError::Aliased { target: "b" }));
+
+        let collision = LabelAlias { name: "other", ..b.add_alias("old").unwrap() };
+        let domain = registry::domain::<Tag>();
+        let error = registry::add_alias(domain, collision.id, collision.name, a.into()).unwrap_err();
+        assert!(matches!(error, LabelAliasError::Collision(LabelCollision { existing: Cow::Borrowed("old"), .. })));
+    }
+}
@@ -0,0 +1,165 @@
+use super::{registry, ConstLabel, LabelDomain, LabelId};
+use crate::hash::{StableHashMap, StableHashSet};
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+
+/// A [`ConstLabel`] declared with [`const_label!`][crate::const_label], collected from every crate
+/// of the program at link time.
+#[derive(Debug)]
+pub struct LabelDeclaration {
+    /// The [`LabelDomain::NAME`] of the domain.
+    pub domain: &'static str,
+    pub id: LabelId,
+    pub name: &'static str,
+    /// Module path of the declaration.
+    pub location: &'static str,
+    /// Tells apart domains of the same name, whose declarations are validated separately.
+    key: fn() -> registry::DomainKey,
+}
+
+impl LabelDeclaration {
+    pub const fn new<Domain: LabelDomain>(label: ConstLabel<Domain>, location: &'static str) -> Self {
+        Self { domain: Domain::NAME, id: label.id, name: label.name, location, key: registry::domain::<Domain> }
+    }
+}
+
+inventory::collect!(LabelDeclaration);
+
+/// Iterates the labels declared with [`const_label!`][crate::const_label] across all linked crates.
+pub fn declared_labels() -> impl Iterator<Item = &'static LabelDeclaration> {
+    inventory::iter::<LabelDeclaration>.into_iter()
+}
+
+/// Names sharing one id within a domain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabelConflict {
+    pub domain: &'static str,
+    pub id: LabelId,
+    /// `(name, location)` pairs, the location is `None` for labels registered at runtime.
+    pub names: Vec<(Cow<'static, str>, Option<&'static str>)>,
+}
+
+/// The result of [`register_declared_labels`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LabelReport {
+    pub domains: usize,
+    pub labels: usize,
+    pub conflicts: Vec<LabelConflict>,
+}
+
+impl LabelReport {
+    pub fn is_ok(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+impl Display for LabelReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} labels in {} domains, {} conflicts", self.labels, self.domains, self.conflicts.len())?;
+        for conflict in &self.conflicts {
+            write!(f, "\n  {} 0x{:08x}:", conflict.domain, conflict.id)?;
+            for (name, location) in &conflict.names {
+                write!(f, " {:?} ({})", name, location.unwrap_or("registered at runtime"))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Validates the whole program's declared label space for id collisions per domain, including collisions
+/// with labels already registered at runtime, and registers every declared label that does not collide,
+/// so that [`Label::from_id`][super::Label::from_id] finds labels that were never converted.
+///
+/// Intended to be called once at startup.
+pub fn register_declared_labels() -> LabelReport {
+    let mut declarations = StableHashMap::<(registry::DomainKey, LabelId), Vec<&LabelDeclaration>>::default();
+    for declaration in declared_labels() {
+        declarations.entry(((declaration.key)(), declaration.id)).or_default().push(declaration);
+    }
+
+    let mut domains = StableHashSet::default();
+    let mut report = LabelReport::default();
+    for ((domain, id), group) in declarations {
+        domains.insert(domain);
+        report.labels += 1;
+        let mut names: Vec<_> =
+            group.iter().map(|declaration| (Cow::Borrowed(declaration.name), Some(declaration.location))).collect();
+        names.sort_unstable();
+        names.dedup_by(|(a, _), (b, _)| a == b);
+        if let [(Cow::Borrowed(name), _)] = names[..] {
+            match registry::intern(domain, id, name) {
+                Ok(_) => continue,
+                Err(existing) => names.push((existing, None)),
+            }
+        }
+        report.conflicts.push(LabelConflict { domain: domain.name, id, names });
+    }
+    report.domains = domains.len();
+    report.conflicts.sort_unstable_by_key(|conflict| (conflict.domain, conflict.id));
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::label::Label;
+
+    struct Declared;
+    impl LabelDomain for Declared {
+        const NAME: &'static str = "xaoc_utils::label::declared::tests::Declared";
+    }
+    struct Conflicting;
+    impl LabelDomain for Conflicting {
+        const NAME: &'static str = "xaoc_utils::label::declared::tests::Conflicting";
+    }
+
+    crate::const_label! {
+        Declared;
+        DECLARED_A = "declared_a";
+        DECLARED_B = "declared_b";
+    }
+
+    mod other_crate {
+        crate::const_label! {
+            super::Declared;
+            pub DECLARED_A_AGAIN = "declared_a";
+        }
+    }
+
+    inventory::submit! {
+        LabelDeclaration { id: 1, name: "first", ..LabelDeclaration::new(ConstLabel::<Conflicting>::new(""), "here") }
+    }
+    inventory::submit! {
+        LabelDeclaration { id: 1, name: "second", ..LabelDeclaration::new(ConstLabel::<Conflicting>::new(""), "there") }
+    }
+    inventory::submit! {
+        LabelDeclaration {
+            name: "declared_runtime",
+            ..LabelDeclaration::new(ConstLabel::<Conflicting>::new("runtime"), "here")
+        }
+    }
+
+    #[test]
+    fn register() {
+        assert!(Label::<Declared>::from_id(DECLARED_B.id()).is_none());
+        let runtime = Label::<Conflicting>::new("runtime");
+
+        let report = register_declared_labels();
+        assert!(!report.is_ok());
+        assert!(report.labels >= 4);
+        assert!(report.domains >= 2);
+        assert_eq!(Label::<Declared>::from_id(DECLARED_B.id()), Some(DECLARED_B.label()));
+        assert_eq!(Label::<Declared>::from_id(other_crate::DECLARED_A_AGAIN.id()), Some(DECLARED_A.label()));
+
+        let conflicts: Vec<_> = report.conflicts.iter().filter(|c| c.domain.ends_with("Conflicting")).collect();
+        assert_eq!(conflicts.len(), 2);
+        let names = |id| {
+            let conflict = conflicts.iter().find(|c| c.id == id).unwrap();
+            conflict.names.iter().map(|(name, location)| (name.as_ref(), *location)).collect::<Vec<_>>()
+        };
+        assert_eq!(names(1), [("first", Some("here")), ("second", Some("there"))]);
+        assert_eq!(names(runtime.id()), [("declared_runtime", Some("here")), ("runtime", None)]);
+        assert!(report.to_string().contains("\"declared_runtime\" (here) \"runtime\" (registered at runtime)"));
+    }
+}
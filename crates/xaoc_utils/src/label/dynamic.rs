@@ -0,0 +1,265 @@
+use super::registry::{self, DynLabelStats, Shared, SharedEntry};
+use super::{label_id, ConstLabel, Label, LabelCollision, LabelDomain, LabelId};
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt::{Debug, Formatter};
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+
+/// A label whose name is reference counted instead of leaked like the names of [`Label::new`].
+/// The name is unregistered and its memory reclaimed when the last `DynLabel` referring to it is dropped,
+/// unless the name is also used by a [`Label`], which makes it `'static`.
+///
+/// Compares and hashes like [`Label`] by id, so labels of both kinds with the same name are equal,
+/// and a [`Label`] converts into a `DynLabel` for free to be used as a key in the same map.
+/// Intended for labels created from user input or asset paths in long running processes.
+pub struct DynLabel<Domain> {
+    id: LabelId,
+    index: u32,
+    name: DynName,
+    _marker: PhantomData<Domain>,
+}
+
+#[derive(Clone)]
+enum DynName {
+    Static(&'static str),
+    Reclaimable(Arc<SharedEntry>),
+}
+
+impl<Domain: LabelDomain> DynLabel<Domain> {
+    pub fn new(name: &str) -> Self {
+        Self::try_new(name).unwrap_or_else(|collision| panic!("{}", collision))
+    }
+
+    /// Like [`DynLabel::new`], but returns an error instead of panicking if the name collides
+    /// with a different name already registered in this domain.
+    pub fn try_new(name: &str) -> Result<Self, LabelCollision> {
+        let id = label_id::<Domain>(name);
+        registry::intern_shared(registry::domain::<Domain>(), id, name)
+            .map(Self::from)
+            .map_err(|existing| LabelCollision { id, name: name.into(), existing })
+    }
+
+    /// Returns the label registered in this domain under the given id, if any.
+    /// Unlike [`Label::from_id`] this does not make reclaimable names `'static`.
+    pub fn from_id(id: LabelId) -> Option<Self> {
+        registry::get_shared(registry::domain::<Domain>(), id, None).map(Self::from)
+    }
+
+    /// Returns the label registered in this domain under the given name, if any.
+    pub fn lookup(name: &str) -> Option<Self> {
+        let id = label_id::<Domain>(name);
+        registry::get_shared(registry::domain::<Domain>(), id, Some(name)).map(Self::from)
+    }
+
+    /// Returns every label registered in this domain, including the ones only alive as `DynLabel`s,
+    /// in registration order.
+    pub fn registered() -> Vec<Self> {
+        registry::shared_entries(registry::domain::<Domain>()).into_iter().map(Self::from).collect()
+    }
+
+    /// Returns the memory currently used by reclaimable names of this domain.
+    pub fn stats() -> DynLabelStats {
+        registry::stats(registry::domain::<Domain>())
+    }
+
+    /// Converts into a [`Label`], making the name `'static` by leaking a copy of it if needed.
+    pub fn to_label(&self) -> Label<Domain> {
+        match &self.name {
+            DynName::Static(name) => Label { id: self.id, index: self.index, name, _marker: PhantomData },
+            DynName::Reclaimable(entry) => Label::new(entry.name.to_string()),
+        }
+    }
+}
+
+impl<Domain> DynLabel<Domain> {
+    #[inline]
+    pub fn id(&self) -> LabelId {
+        self.id
+    }
+
+    /// See [`Label::index`]. Once the name is reclaimed, its index is reused for the next new name of the domain.
+    #[inline]
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    #[inline]
+    pub fn name(&self) -> &str {
+        match &self.name {
+            DynName::Static(name) => name,
+            DynName::Reclaimable(entry) => &entry.name,
+        }
+    }
+
+    /// Returns `true` if the name will be reclaimed once no handle refers to it.
+    pub fn is_reclaimable(&self) -> bool {
+        matches!(self.name, DynName::Reclaimable(_))
+    }
+}
+
+impl<Domain> From<Shared> for DynLabel<Domain> {
+    fn from(shared: Shared) -> Self {
+        match shared {
+            Shared::Static(record) => Label::from(record).into(),
+            Shared::Reclaimable(entry) => {
+                Self { id: entry.id, index: entry.index, name: DynName::Reclaimable(entry), _marker: PhantomData }
+            }
+        }
+    }
+}
+
+impl<Domain> From<Label<Domain>> for DynLabel<Domain> {
+    fn from(label: Label<Domain>) -> Self {
+        Self { id: label.id, index: label.index, name: DynName::Static(label.name), _marker: PhantomData }
+    }
+}
+
+impl<Domain: LabelDomain> From<ConstLabel<Domain>> for DynLabel<Domain> {
+    fn from(label: ConstLabel<Domain>) -> Self {
+        label.label().into()
+    }
+}
+
+impl<Domain> Clone for DynLabel<Domain> {
+    fn clone(&self) -> Self {
+        Self { id: self.id, index: self.index, name: self.name.clone(), _marker: PhantomData }
+    }
+}
+
+impl<Domain> Debug for DynLabel<Domain> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "DynLabel<{}>({:?}, 0x{:08x})", core::any::type_name::<Domain>(), self.name(), self.id)
+    }
+}
+
+impl<Domain> PartialEq for DynLabel<Domain> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+impl<Domain> Eq for DynLabel<Domain> {}
+impl<Domain> Hash for DynLabel<Domain> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state)
+    }
+}
+
+impl<Domain> PartialEq<Label<Domain>> for DynLabel<Domain> {
+    fn eq(&self, other: &Label<Domain>) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<Domain> PartialEq<DynLabel<Domain>> for Label<Domain> {
+    fn eq(&self, other: &DynLabel<Domain>) -> bool {
+        self.id == other.id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::StableHashMap;
+
+    #[test]
+    fn reclaim() {
+        struct Tag;
+        impl LabelDomain for Tag {
+            const NAME: &'static str = "xaoc_utils::label::dynamic::tests::reclaim::Tag";
+        }
+
+        let a = DynLabel::<Tag>::new("dyn_a");
+        assert!(a.is_reclaimable());
+        assert_eq!(DynLabel::<Tag>::stats(), DynLabelStats { labels: 1, bytes: 5, ..Default::default() });
+        let a2 = DynLabel::<Tag>::new(&String::from("dyn_a"));
+        assert_eq!(a, a2);
+        assert_eq!(DynLabel::<Tag>::lookup("dyn_a"), Some(a.clone()));
+        assert_eq!(DynLabel::<Tag>::registered(), std::slice::from_ref(&a));
+        assert!(Label::<Tag>::registered().is_empty());
+
+        let index = a.index();
+        drop(a);
+        drop(a2);
+        let stats = DynLabel::<Tag>::stats();
+        assert_eq!((stats.labels, stats.bytes, stats.reclaimed), (0, 0, 1));
+        assert!(stats.reclaimed_bytes > 0);
+        assert!(DynLabel::<Tag>::registered().is_empty());
+        assert_eq!(DynLabel::<Tag>::lookup("dyn_a"), None);
+        assert_eq!(Label::<Tag>::lookup("dyn_a"), None);
+
+        let other = DynLabel::<Tag>::new("dyn_b");
+        assert_eq!(other.index(), index);
+        assert_eq!(DynLabel::<Tag>::stats(), DynLabelStats { labels: 1, bytes: 5, ..Default::default() });
+        assert_eq!(DynLabel::<Tag>::registered(), [other]);
+    }
+
+    #[test]
+    fn bounded() {
+        struct Tag;
+        impl LabelDomain for Tag {
+            const NAME: &'static str = "xaoc_utils::label::dynamic::tests::bounded::Tag";
+        }
+
+        let kept = Label::<Tag>::new("kept");
+        let mut alive = Vec::new();
+        for i in 0..10_000 {
+            alive.push(DynLabel::<Tag>::new(&format!("asset/{i}")));
+            if alive.len() > 8 {
+                alive.remove(0);
+            }
+            assert!(alive.iter().all(|label| label.index() <= 9));
+        }
+        assert_eq!(DynLabel::<Tag>::stats().labels, 8);
+        assert_eq!(DynLabel::<Tag>::stats().reclaimed, 1);
+        drop(alive);
+        assert_eq!(DynLabel::<Tag>::stats().reclaimed, 9);
+        assert_eq!(DynLabel::<Tag>::registered(), [DynLabel::from(kept)]);
+        assert!(Label::<Tag>::from_index(1).is_none());
+        assert_eq!(Label::<Tag>::from_index(kept.index()), Some(kept));
+    }
+
+    #[test]
+    fn mixed_with_label() {
+        struct Tag;
+        impl LabelDomain for Tag {
+            const NAME: &'static str = "xaoc_utils::label::dynamic::tests::mixed_with_label::Tag";
+        }
+
+        let dynamic = DynLabel::<Tag>::new("mixed_a");
+        let promoted = Label::<Tag>::new("mixed_a");
+        assert_eq!(promoted, dynamic);
+        assert_eq!(promoted.index(), dynamic.index());
+        assert!(!DynLabel::<Tag>::new("mixed_a").is_reclaimable());
+        assert_eq!(DynLabel::<Tag>::stats().labels, 0);
+        drop(dynamic);
+        assert_eq!(Label::<Tag>::lookup("mixed_a"), Some(promoted));
+
+        let mut map = StableHashMap::default();
+        map.insert(DynLabel::from(promoted), 1);
+        map.insert(DynLabel::new("mixed_b"), 2);
+        assert_eq!(map[&DynLabel::from(promoted)], 1);
+        assert_eq!(map[&DynLabel::new("mixed_b")], 2);
+
+        let from_id = Label::<Tag>::from_id(DynLabel::<Tag>::new("mixed_c").id());
+        assert_eq!(from_id.map(|label| label.name), Some("mixed_c"));
+    }
+
+    #[test]
+    fn collision() {
+        struct Tag;
+        impl LabelDomain for Tag {
+            const NAME: &'static str = "xaoc_utils::label::dynamic::tests::collision::Tag";
+        }
+
+        let id = label_id::<Tag>("collision_a");
+        let _held = match registry::intern_shared(registry::domain::<Tag>(), id, "collision_b").unwrap() {
+            Shared::Reclaimable(entry) => entry,
+            Shared::Static(_) => unreachable!(),
+        };
+        let error = DynLabel::<Tag>::try_new("collision_a").unwrap_err();
+        assert_eq!(error.existing, "collision_b");
+        assert!(Label::<Tag>::try_new("collision_a").is_err());
+    }
+}
@@ -0,0 +1,173 @@
+use super::ConstLabel;
+
+/// Declares [`ConstLabel`] constants of a single domain, rejecting at compile time any two names
+/// in the same invocation that are equal or whose ids collide.
+/// The labels are also collected at link time, see [`register_declared_labels`][super::register_declared_labels].
+///
+/// ```
+/// use xaoc_utils::const_label;
+/// use xaoc_utils::label::{ConstLabel, LabelDomain};
+///
+/// struct SystemDomain;
+/// impl LabelDomain for SystemDomain {
+///     const NAME: &'static str = "my_game::systems";
+/// }
+///
+/// const_label! {
+///     SystemDomain;
+///     /// Everything that renders.
+///     pub RENDER = "render";
+///     pub(crate) SHADOW = "render/shadow";
+/// }
+///
+/// assert_eq!(SHADOW.label().parent(), Some(RENDER.label()));
+/// ```
+///
+/// Duplicated names fail to compile:
+///
+/// ```compile_fail
+/// # use xaoc_utils::const_label;
+/// # struct SystemDomain;
+/// # impl xaoc_utils::label::LabelDomain for SystemDomain { const NAME: &'static str = "my_game::systems"; }
+/// const_label! {
+///     SystemDomain;
+///     RENDER = "render";
+///     ALSO_RENDER = "render";
+/// }
+/// ```
+///
+/// Only the labels of one invocation are checked against each other at compile time, collisions with
+/// labels declared elsewhere are reported by [`register_declared_labels`][super::register_declared_labels].
+#[macro_export]
+macro_rules! const_label {
+    ($domain:ty; $($(#[$meta:meta])* $vis:vis $name:ident = $value:literal;)+) => {
+        $(
+            $(#[$meta])*
+            $vis const $name: $crate::label::ConstLabel<$domain> = $crate::label::ConstLabel::new($value);
+
+            $crate::label::__private::inventory::submit! {
+                $crate::label::LabelDeclaration::new($name, module_path!())
+            }
+        )+
+
+        const _: () = {
+            const LABELS: &[$crate::label::ConstLabel<$domain>] = &[$($name),+];
+            $(
+                assert!(
+                    $crate::label::count_same_name(LABELS, &$name) == 1,
+                    concat!("duplicate label name ", stringify!($value), " of ", stringify!($name)),
+                );
+                assert!(
+                    $crate::label::count_same_id(LABELS, &$name) == 1,
+                    concat!("label id of ", stringify!($name), " = ", stringify!($value), " collides with another label"),
+                );
+            )+
+        };
+    };
+}
+
+/// Creates a [`Label`][super::Label] of the domain, computing its id at compile time.
+///
+/// ```
+/// use xaoc_utils::label;
+/// use xaoc_utils::label::{Label, LabelDomain};
+///
+/// struct AssetDomain;
+/// impl LabelDomain for AssetDomain {
+///     const NAME: &'static str = "my_game::assets";
+/// }
+///
+/// assert_eq!(label!(AssetDomain, "textures/grass"), Label::<AssetDomain>::new("textures/grass"));
+/// ```
+#[macro_export]
+macro_rules! label {
+    ($domain:ty, $value:literal) => {{
+        const LABEL: $crate::label::ConstLabel<$domain> = $crate::label::ConstLabel::new($value);
+        LABEL.label()
+    }};
+}
+
+#[doc(hidden)]
+pub const fn count_same_name<Domain>(labels: &[ConstLabel<Domain>], label: &ConstLabel<Domain>) -> usize {
+    let mut count = 0;
+    let mut i = 0;
+    while i < labels.len() {
+        if str_eq(labels[i].name, label.name) {
+            count += 1;
+        }
+        i += 1;
+    }
+    count
+}
+
+#[doc(hidden)]
+pub const fn count_same_id<Domain>(labels: &[ConstLabel<Domain>], label: &ConstLabel<Domain>) -> usize {
+    let mut count = 0;
+    let mut i = 0;
+    while i < labels.len() {
+        if labels[i].id == label.id {
+            count += 1;
+        }
+        i += 1;
+    }
+    count
+}
+
+const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::label::LabelDomain;
+    use std::marker::PhantomData;
+
+    struct Tag;
+    impl LabelDomain for Tag {
+        const NAME: &'static str = "xaoc_utils::label::macros::tests::Tag";
+    }
+
+    #[test]
+    fn duplicates() {
+        const A: ConstLabel<Tag> = ConstLabel::new("a");
+        const B: ConstLabel<Tag> = ConstLabel::new("b");
+        const FAKE_COLLISION: ConstLabel<Tag> = ConstLabel { id: A.id, name: "c", _marker: PhantomData };
+
+        assert_eq!(count_same_name(&[A, B], &A), 1);
+        assert_eq!(count_same_name(&[A, B, A], &A), 2);
+        assert_eq!(count_same_id(&[A, B], &B), 1);
+        assert_eq!(count_same_id(&[A, B, FAKE_COLLISION], &A), 2);
+        assert_eq!(count_same_name(&[A, B, FAKE_COLLISION], &A), 1);
+    }
+
+    mod declared {
+        pub struct Domain;
+        impl crate::label::LabelDomain for Domain {
+            const NAME: &'static str = "xaoc_utils::label::macros::tests::declared::Domain";
+        }
+
+        crate::const_label! {
+            Domain;
+            pub FIRST = "first";
+            pub SECOND = "second";
+        }
+    }
+
+    #[test]
+    fn declared() {
+        assert_eq!(declared::FIRST.label(), crate::label!(declared::Domain, "first"));
+        assert_ne!(declared::FIRST.label(), declared::SECOND.label());
+    }
+}
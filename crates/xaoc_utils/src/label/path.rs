@@ -0,0 +1,123 @@
+use super::{continue_id, registry, Label, LabelDomain, SEPARATOR};
+use alloc::format;
+use alloc::vec::Vec;
+
+/// Path-style hierarchy of labels, where the segments of a name are separated by [`SEPARATOR`],
+/// e.g. `"render/main/shadow"` is a child of `"render/main"`.
+impl<Domain: LabelDomain> Label<Domain> {
+    /// Returns the label this one is nested in, or `None` for a top level label.
+    /// The parent is registered if it was not before, without allocating.
+    pub fn parent(&self) -> Option<Self> {
+        let (parent, _) = self.name.rsplit_once(SEPARATOR)?;
+        Some(Label::new(parent))
+    }
+
+    /// Returns the label nested in this one under `segment`.
+    /// Only allocates the first time the child is registered.
+    pub fn child(&self, segment: &str) -> Self {
+        let mut separator = [0; 4];
+        let id = continue_id(self.id, SEPARATOR.encode_utf8(&mut separator).as_bytes());
+        let id = continue_id(id, segment.as_bytes());
+        let is_child = |name: &str| {
+            name.len() == self.name.len() + SEPARATOR.len_utf8() + segment.len()
+                && name.starts_with(self.name)
+                && name[self.name.len()..].starts_with(SEPARATOR)
+                && name.ends_with(segment)
+        };
+        match registry::get(registry::domain::<Domain>(), id, None) {
+            Some(record) if is_child(record.name) => record.into(),
+            _ => Label::new(format!("{}{}{}", self.name, SEPARATOR, segment)),
+        }
+    }
+
+    /// The last segment of the name.
+    pub fn leaf(&self) -> &str {
+        self.name.rsplit(SEPARATOR).next().unwrap_or(self.name)
+    }
+
+    /// Iterates the segments of the name, from the top level down.
+    pub fn segments(&self) -> impl Iterator<Item = &str> {
+        self.name.split(SEPARATOR)
+    }
+
+    /// Returns `true` if this label is nested, at any depth, in `ancestor`.
+    pub fn is_descendant_of(&self, ancestor: &Self) -> bool {
+        is_under(self.name, ancestor.name)
+    }
+
+    /// Returns every registered label nested, at any depth, in this one.
+    pub fn descendants(&self) -> Vec<Self> {
+        Self::registered_under(self.name)
+    }
+
+    /// Returns every registered label nested directly in this one.
+    pub fn children(&self) -> Vec<Self> {
+        let mut children = self.descendants();
+        children.retain(|child| !child.name[self.name.len() + SEPARATOR.len_utf8()..].contains(SEPARATOR));
+        children
+    }
+
+    /// Returns every registered label of this domain nested, at any depth, under the `prefix` path,
+    /// in registration order. The `prefix` itself does not have to be a registered label.
+    pub fn registered_under(prefix: &str) -> Vec<Self> {
+        let prefix = prefix.trim_end_matches(SEPARATOR);
+        registry::entries(registry::domain::<Domain>())
+            .into_iter()
+            .filter(|record| is_under(record.name, prefix))
+            .map(Self::from)
+            .collect()
+    }
+}
+
+fn is_under(name: &str, prefix: &str) -> bool {
+    name.len() > prefix.len() && name.starts_with(prefix) && name[prefix.len()..].starts_with(SEPARATOR)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::label::ConstLabel;
+
+    struct Tag;
+    impl LabelDomain for Tag {
+        const NAME: &'static str = "xaoc_utils::label::path::tests::Tag";
+    }
+
+    #[test]
+    fn parent_and_child() {
+        const SHADOW: ConstLabel<Tag> = ConstLabel::new("render/main/shadow");
+
+        let shadow = SHADOW.label();
+        let main = shadow.parent().unwrap();
+        assert_eq!(main.name(), "render/main");
+        assert_eq!(main.parent().unwrap().name(), "render");
+        assert_eq!(main.parent().unwrap().parent(), None);
+        assert_eq!(main.child("shadow"), shadow);
+        assert_eq!(main.child("shadow").name(), "render/main/shadow");
+        assert_eq!(main.child("ui").id(), Label::<Tag>::new("render/main/ui").id());
+        assert_eq!(shadow.leaf(), "shadow");
+        assert_eq!(shadow.segments().collect::<Vec<_>>(), ["render", "main", "shadow"]);
+        assert!(shadow.is_descendant_of(&main));
+        assert!(!main.is_descendant_of(&shadow));
+        assert!(!Label::<Tag>::new("render_other").is_descendant_of(&main.parent().unwrap()));
+    }
+
+    #[test]
+    fn subtrees() {
+        struct Tree;
+        impl LabelDomain for Tree {
+            const NAME: &'static str = "xaoc_utils::label::path::tests::subtrees::Tree";
+        }
+        let root = Label::<Tree>::new("root");
+        let a = root.child("a");
+        let b = a.child("b");
+        let c = root.child("c");
+        Label::<Tree>::new("rooted/d");
+
+        assert_eq!(root.descendants(), [a, b, c]);
+        assert_eq!(root.children(), [a, c]);
+        assert_eq!(a.descendants(), [b]);
+        assert_eq!(Label::<Tree>::registered_under("root/"), [a, b, c]);
+        assert!(b.descendants().is_empty());
+    }
+}
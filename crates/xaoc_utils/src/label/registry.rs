@@ -0,0 +1,739 @@
+use super::{LabelAlias, LabelAliasError, LabelAliasHook, LabelCollision, LabelDomain, LabelId};
+use crate::hash::{HashMap, PassHash, TypeIdMap};
+use crate::sync::{Lazy, RwLock};
+use alloc::borrow::Cow;
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::string::ToString;
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+use core::any::TypeId;
+use core::fmt::{Display, Formatter};
+use hashbrown::hash_map::Entry;
+use once_cell::race::OnceRef;
+
+/// Identifies a domain in the registry: by its `TypeId` within this copy of the crate, and by its
+/// [`LabelDomain::NAME`] in calls from other copies, e.g. from a plugin, where the `TypeId` differs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DomainKey {
+    pub name: &'static str,
+    /// `None` once the key crossed into another copy of the crate.
+    type_id: Option<TypeId>,
+    /// Only used to report domains sharing a name.
+    type_name: &'static str,
+}
+
+#[inline]
+pub fn domain<Domain: LabelDomain>() -> DomainKey {
+    DomainKey { name: Domain::NAME, type_id: Some(TypeId::of::<Domain>()), type_name: core::any::type_name::<Domain>() }
+}
+
+/// A label with a `'static` name as stored in the registry.
+#[derive(Debug, Clone, Copy)]
+pub struct Record {
+    pub id: LabelId,
+    /// Dense index assigned in registration order, unique among the live labels of the domain.
+    pub index: u32,
+    pub name: &'static str,
+}
+
+/// The registry entry of a reclaimable label name, shared by all its [`DynLabel`][super::DynLabel]s.
+/// The name is unregistered when the last handle is dropped.
+#[derive(Debug)]
+pub struct SharedEntry {
+    domain: DomainKey,
+    pub id: LabelId,
+    pub index: u32,
+    pub name: Arc<str>,
+}
+
+impl Drop for SharedEntry {
+    fn drop(&mut self) {
+        release(self);
+    }
+}
+
+/// A registered name as handed out to [`DynLabel`][super::DynLabel]s.
+#[derive(Debug, Clone)]
+pub enum Shared {
+    Static(Record),
+    Reclaimable(Arc<SharedEntry>),
+}
+
+enum Name {
+    Static(&'static str),
+    /// Only alive as long as the entry, the name is kept here to compare names without upgrading.
+    Reclaimable {
+        name: Arc<str>,
+        entry: Weak<SharedEntry>,
+    },
+}
+
+struct Slot {
+    index: u32,
+    name: Name,
+}
+
+impl Slot {
+    /// The name, unless it was or is being reclaimed.
+    fn name(&self) -> Option<&str> {
+        match &self.name {
+            Name::Static(name) => Some(name),
+            Name::Reclaimable { name, entry } if entry.strong_count() > 0 => Some(name),
+            _ => None,
+        }
+    }
+}
+
+/// Memory used by the reclaimable label names of a domain.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DynLabelStats {
+    /// Number of names only referenced by [`DynLabel`][super::DynLabel]s.
+    pub labels: usize,
+    /// Total length of those names in bytes.
+    pub bytes: usize,
+    /// Number of dense indices freed by reclaimed names and not reused by a new name yet.
+    pub reclaimed: usize,
+    /// Bytes kept for those indices until they are reused.
+    pub reclaimed_bytes: usize,
+}
+
+impl DynLabelStats {
+    fn set_reclaimed(&mut self, reclaimed: usize) {
+        self.reclaimed = reclaimed;
+        self.reclaimed_bytes = reclaimed * (core::mem::size_of::<Option<LabelId>>() + core::mem::size_of::<u32>());
+    }
+}
+
+/// All labels registered for a single domain.
+#[derive(Default)]
+struct DomainTable {
+    slots: hashbrown::HashMap<LabelId, Slot, PassHash>,
+    /// Ids by dense index, used for enumeration, `None` at the indices freed by reclaimed names.
+    order: Vec<Option<LabelId>>,
+    /// The freed indices, reused before new ones so that the table stays as large as the most labels alive at once.
+    free: Vec<u32>,
+    stats: DynLabelStats,
+    /// Old names by id, which never have a live name of their own.
+    aliases: hashbrown::HashMap<LabelId, LabelAlias, PassHash>,
+}
+
+/// What an id without a reclaimable name resolves to.
+enum Found {
+    Label(Record),
+    Alias(LabelAlias),
+}
+
+impl DomainTable {
+    fn record(id: LabelId, slot: &Slot) -> Option<Record> {
+        match slot.name {
+            Name::Static(name) => Some(Record { id, index: slot.index, name }),
+            _ => None,
+        }
+    }
+
+    /// The dense index of the id, the one it will get if it is not registered.
+    fn index(&self, id: LabelId) -> u32 {
+        match self.slots.get(&id) {
+            Some(slot) => slot.index,
+            None => match self.free.last() {
+                Some(index) => *index,
+                None => u32::try_from(self.order.len()).expect("too many labels in a single domain"),
+            },
+        }
+    }
+
+    /// Sets the name of the id, whose current name must be absent, reclaimed or equal to the new one.
+    fn set_name(&mut self, id: LabelId, name: Name) -> &Slot {
+        let index = self.index(id);
+        if let Name::Reclaimable { name, .. } = &name {
+            self.stats.labels += 1;
+            self.stats.bytes += name.len();
+        }
+        match self.slots.entry(id) {
+            Entry::Occupied(slot) => {
+                let slot = slot.into_mut();
+                if let Name::Reclaimable { name, .. } = core::mem::replace(&mut slot.name, name) {
+                    self.stats.labels -= 1;
+                    self.stats.bytes -= name.len();
+                }
+                slot
+            }
+            Entry::Vacant(slot) => {
+                if self.free.pop().is_some() {
+                    self.order[index as usize] = Some(id);
+                    self.stats.set_reclaimed(self.free.len());
+                } else {
+                    self.order.push(Some(id));
+                }
+                slot.insert(Slot { index, name })
+            }
+        }
+    }
+
+    /// Unregisters the reclaimed name of the id and frees its dense index for the next new name.
+    fn vacate(&mut self, id: LabelId) {
+        let Some(slot) = self.slots.remove(&id) else { return };
+        if let Name::Reclaimable { name, .. } = slot.name {
+            self.stats.labels -= 1;
+            self.stats.bytes -= name.len();
+        }
+        self.order[slot.index as usize] = None;
+        self.free.push(slot.index);
+        self.stats.set_reclaimed(self.free.len());
+    }
+
+    /// The `'static` name of the id, or its alias if it has no live name, `None` if it has a reclaimable one.
+    fn find_static(&self, id: LabelId) -> Option<Found> {
+        match self.slots.get(&id) {
+            Some(slot) if slot.name().is_some() => Self::record(id, slot).map(Found::Label),
+            _ => self.aliases.get(&id).copied().map(Found::Alias),
+        }
+    }
+
+    fn intern(
+        &mut self,
+        id: LabelId,
+        name: &str,
+        leak: impl FnOnce() -> &'static str,
+    ) -> Result<Found, Cow<'static, str>> {
+        if let Some(found) = self.find_static(id) {
+            return Ok(found);
+        }
+        if let Some(existing) = self.slots.get(&id).and_then(Slot::name).filter(|existing| *existing != name) {
+            return Err(Cow::Owned(existing.to_owned()));
+        }
+        Ok(Found::Label(Self::record(id, self.set_name(id, Name::Static(leak()))).unwrap()))
+    }
+}
+
+/// Returns the already registered record, or `Err` with its name if it differs from `name`.
+fn check_duplicate(name: &str, existing: Record) -> Result<Record, Cow<'static, str>> {
+    if existing.name == name {
+        Ok(existing)
+    } else {
+        Err(Cow::Borrowed(existing.name))
+    }
+}
+
+/// The registry operations of one copy of this crate. Plugins loaded as dynamic libraries link their own copy,
+/// with its own registry, and call into the operations of the host once installed.
+#[repr(C)]
+struct Operations {
+    /// Must stay the first field, as it is read before knowing whether the rest of the layout matches.
+    abi: u64,
+    intern: fn(DomainKey, LabelId, &'static str) -> Result<Record, Cow<'static, str>>,
+    intern_owned: fn(DomainKey, LabelId, &str) -> Result<Record, Cow<'static, str>>,
+    intern_shared: fn(DomainKey, LabelId, &str) -> Result<Shared, Cow<'static, str>>,
+    release: fn(&SharedEntry),
+    get_shared: fn(DomainKey, LabelId, Option<&str>) -> Option<Shared>,
+    id_by_index: fn(DomainKey, u32) -> Option<LabelId>,
+    entries: fn(DomainKey) -> Vec<Record>,
+    ids: fn(DomainKey) -> Vec<LabelId>,
+    stats: fn(DomainKey) -> DynLabelStats,
+    add_alias: fn(DomainKey, LabelId, &str, Record) -> Result<LabelAlias, LabelAliasError>,
+    aliases: fn(DomainKey) -> Vec<LabelAlias>,
+    set_alias_hook: fn(Option<LabelAliasHook>),
+}
+
+/// Changes whenever the crate version, the label id width or the layout of what crosses the boundary does.
+/// It can't tell compilers or dependency versions apart, so plugins must be built like the host.
+const ABI: u64 = {
+    let sizes = [
+        core::mem::size_of::<LabelId>(),
+        core::mem::size_of::<DomainKey>(),
+        core::mem::size_of::<Record>(),
+        core::mem::size_of::<Shared>(),
+        core::mem::size_of::<SharedEntry>(),
+        core::mem::size_of::<DynLabelStats>(),
+        core::mem::size_of::<LabelAlias>(),
+        core::mem::size_of::<LabelAliasError>(),
+        core::mem::size_of::<Operations>(),
+    ];
+    let version = env!("CARGO_PKG_VERSION").as_bytes();
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let mut i = 0;
+    while i < version.len() + sizes.len() {
+        hash ^= if i < version.len() { version[i] as u64 } else { sizes[i - version.len()] as u64 };
+        hash = hash.wrapping_mul(0x100000001b3);
+        i += 1;
+    }
+    hash
+};
+
+const LOCAL_OPERATIONS: Operations = Operations {
+    abi: ABI,
+    intern: local::intern,
+    intern_owned: local::intern_owned,
+    intern_shared: local::intern_shared,
+    release: local::release,
+    get_shared: local::get_shared,
+    id_by_index: local::id_by_index,
+    entries: local::entries,
+    ids: local::ids,
+    stats: local::stats,
+    add_alias: local::add_alias,
+    aliases: local::aliases,
+    set_alias_hook: local::set_alias_hook,
+};
+
+static LOCAL: Operations = LOCAL_OPERATIONS;
+
+static INSTALLED: OnceRef<'static, Operations> = OnceRef::new();
+
+#[inline]
+fn operations() -> &'static Operations {
+    INSTALLED.get().unwrap_or(&LOCAL)
+}
+
+/// The key of the domain for the registry in use, without the `TypeId` of this copy of the crate
+/// if that is an installed one.
+#[inline]
+fn key(domain: DomainKey) -> DomainKey {
+    match INSTALLED.get() {
+        Some(_) => DomainKey { type_id: None, ..domain },
+        None => domain,
+    }
+}
+
+/// A handle to the label registry of a program, for sharing it with plugins loaded as dynamic libraries.
+///
+/// Crates linked into the program through the `dynamic` dylib share its registry already, but a plugin built
+/// as a `cdylib` statically links its own copy of this crate, and so its own registry, in which labels created
+/// by the plugin would be invisible to the host. The host passes its handle to the plugin, which installs it
+/// with [`install_label_registry`] before using any label:
+///
+/// ```
+/// use xaoc_utils::label::{install_label_registry, InstallRegistryError, LabelRegistry};
+///
+/// // Exported by the plugin, looked up and called by the host right after loading it.
+/// #[no_mangle]
+/// pub fn xaoc_install_label_registry(registry: LabelRegistry) -> Result<(), InstallRegistryError> {
+///     install_label_registry(registry)
+/// }
+/// ```
+///
+/// The host and the plugin tell domains apart by their [`LabelDomain::NAME`], as their `TypeId`s differ.
+/// Both sides must be built by the same compiler with the same dependency versions and use the same
+/// global allocator, as names and handles allocated on one side may be freed on the other.
+/// Plugins must not be unloaded, as the registry may refer to names in their static memory.
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct LabelRegistry(&'static Operations);
+
+impl LabelRegistry {
+    /// The registry this copy of the crate uses, which is the installed one if any.
+    pub fn current() -> Self {
+        Self(operations())
+    }
+}
+
+impl PartialEq for LabelRegistry {
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::eq(self.0, other.0)
+    }
+}
+
+impl Eq for LabelRegistry {}
+
+impl core::fmt::Debug for LabelRegistry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "LabelRegistry({:p})", self.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstallRegistryError {
+    /// The registry comes from an incompatible build of this crate.
+    AbiMismatch,
+    /// Another registry was installed before.
+    AlreadyInstalled,
+    /// Labels were registered in the local registry before, and would stay invisible to the host.
+    LabelsRegistered,
+}
+
+impl Display for InstallRegistryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::AbiMismatch => "the label registry comes from an incompatible build of xaoc_utils",
+            Self::AlreadyInstalled => "another label registry is already installed",
+            Self::LabelsRegistered => "labels were registered before installing the label registry",
+        })
+    }
+}
+
+impl core::error::Error for InstallRegistryError {}
+
+/// Makes this copy of the crate register and look up labels in the given registry, typically the host's,
+/// instead of its own. Installing the registry this copy already uses does nothing.
+/// See [`LabelRegistry`].
+pub fn install_label_registry(registry: LabelRegistry) -> Result<(), InstallRegistryError> {
+    if registry == LabelRegistry::current() {
+        return Ok(());
+    }
+    if registry.0.abi != ABI {
+        return Err(InstallRegistryError::AbiMismatch);
+    }
+    if !local::is_empty() {
+        return Err(InstallRegistryError::LabelsRegistered);
+    }
+    INSTALLED.set(registry.0).map_err(|_| InstallRegistryError::AlreadyInstalled)
+}
+
+/// Registers a `'static` name and returns the interned record for the id.
+/// Returns `Err` with the registered name if a different name already has the id.
+#[inline]
+pub fn intern(domain: DomainKey, id: LabelId, name: &'static str) -> Result<Record, Cow<'static, str>> {
+    (operations().intern)(key(domain), id, name)
+}
+
+/// Registers a leaked copy of a non-`'static` name, allocating only if the id has no `'static` name yet.
+/// Returns `Err` with the registered name if a different name already has the id.
+#[inline]
+pub fn intern_owned(domain: DomainKey, id: LabelId, name: &str) -> Result<Record, Cow<'static, str>> {
+    (operations().intern_owned)(key(domain), id, name)
+}
+
+/// Registers a name that is reclaimed once all the returned handles are dropped,
+/// unless the name is or becomes `'static`.
+/// Returns `Err` with the registered name if a different name already has the id.
+#[inline]
+pub fn intern_shared(domain: DomainKey, id: LabelId, name: &str) -> Result<Shared, Cow<'static, str>> {
+    (operations().intern_shared)(key(domain), id, name)
+}
+
+fn release(entry: &SharedEntry) {
+    (operations().release)(entry)
+}
+
+/// Returns the record registered for the id, if any, and if given, only if registered with that name.
+/// A reclaimable name is made `'static` by leaking a copy of it.
+pub fn get(domain: DomainKey, id: LabelId, name: Option<&str>) -> Option<Record> {
+    match get_shared(domain, id, name)? {
+        Shared::Static(record) => Some(record),
+        Shared::Reclaimable(entry) => intern_owned(domain, id, &entry.name).ok(),
+    }
+}
+
+/// Returns the name registered for the id, if any, and if given, only if registered with that name,
+/// without making reclaimable names `'static`.
+#[inline]
+pub fn get_shared(domain: DomainKey, id: LabelId, name: Option<&str>) -> Option<Shared> {
+    (operations().get_shared)(key(domain), id, name)
+}
+
+/// Returns the record registered with the dense index, if any.
+/// A reclaimable name is made `'static` by leaking a copy of it.
+pub fn get_by_index(domain: DomainKey, index: u32) -> Option<Record> {
+    get(domain, (operations().id_by_index)(key(domain), index)?, None)
+}
+
+/// Returns every record with a `'static` name of the domain in registration order.
+pub fn entries(domain: DomainKey) -> Vec<Record> {
+    (operations().entries)(key(domain))
+}
+
+/// Returns every registered name of the domain in registration order, including reclaimable ones.
+pub fn shared_entries(domain: DomainKey) -> Vec<Shared> {
+    // Upgraded entries must not be dropped while the registry is locked, so look them up one by one.
+    (operations().ids)(key(domain)).into_iter().filter_map(|id| get_shared(domain, id, None)).collect()
+}
+
+pub fn stats(domain: DomainKey) -> DynLabelStats {
+    (operations().stats)(key(domain))
+}
+
+/// Registers `name` as an old name of the label, see [`Label::add_alias`][super::Label::add_alias].
+pub fn add_alias(domain: DomainKey, id: LabelId, name: &str, target: Record) -> Result<LabelAlias, LabelAliasError> {
+    (operations().add_alias)(key(domain), id, name, target)
+}
+
+/// Returns every alias of the domain.
+pub fn aliases(domain: DomainKey) -> Vec<LabelAlias> {
+    (operations().aliases)(key(domain))
+}
+
+pub fn set_alias_hook(hook: Option<LabelAliasHook>) {
+    (operations().set_alias_hook)(hook)
+}
+
+/// The registry of this copy of the crate.
+mod local {
+    use super::*;
+
+    /// The tables of the domains, by `TypeId` and by name.
+    #[derive(Default)]
+    struct Registry {
+        tables: TypeIdMap<DomainTable>,
+        /// The `TypeId` and type name of the domain registered with each name.
+        names: HashMap<&'static str, (TypeId, &'static str)>,
+        /// The tables of domains only used by other copies of the crate so far, by name.
+        foreign: HashMap<&'static str, DomainTable>,
+    }
+
+    impl Registry {
+        fn is_empty(&self) -> bool {
+            self.tables.is_empty() && self.foreign.is_empty()
+        }
+
+        fn table(&self, domain: DomainKey) -> Option<&DomainTable> {
+            match (domain.type_id, self.names.get(domain.name)) {
+                (Some(type_id), Some(_)) | (None, Some(&(type_id, _))) => self.tables.get(&type_id),
+                (_, None) => self.foreign.get(domain.name),
+            }
+        }
+
+        /// The table of the domain, registering the domain with its name if needed.
+        /// Panics if another domain of this copy of the crate has the same name.
+        fn table_mut(&mut self, domain: DomainKey) -> &mut DomainTable {
+            let Some(type_id) = domain.type_id.or_else(|| self.names.get(domain.name).map(|(type_id, _)| *type_id))
+            else {
+                return self.foreign.entry(domain.name).or_default();
+            };
+            match self.names.entry(domain.name) {
+                Entry::Occupied(entry) => {
+                    let (registered, type_name) = *entry.get();
+                    assert!(
+                        registered == type_id,
+                        "label domains {type_name} and {} have the same name {:?}",
+                        domain.type_name,
+                        domain.name
+                    );
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert((type_id, domain.type_name));
+                    if let Some(table) = self.foreign.remove(domain.name) {
+                        self.tables.insert(type_id, table);
+                    }
+                }
+            }
+            self.tables.entry(type_id).or_default()
+        }
+    }
+
+    static REGISTRY: Lazy<RwLock<Registry>> = Lazy::new(|| RwLock::new(Registry::default()));
+
+    static ALIAS_HOOK: RwLock<Option<LabelAliasHook>> = RwLock::new(None);
+
+    pub fn is_empty() -> bool {
+        REGISTRY.read().is_empty()
+    }
+
+    /// Resolves an alias found for the id of `name`, or returns `Err` with the alias name if `name` only collides
+    /// with it. Runs the alias hook, so the registry must not be locked.
+    fn use_alias(name: Option<&str>, alias: LabelAlias) -> Result<Record, Cow<'static, str>> {
+        if name.is_some_and(|name| name != alias.name) {
+            return Err(Cow::Borrowed(alias.name));
+        }
+        if let Some(hook) = *ALIAS_HOOK.read() {
+            hook(&alias);
+        }
+        Ok(alias.target_record())
+    }
+
+    fn resolve(name: &str, found: Found) -> Result<Record, Cow<'static, str>> {
+        match found {
+            Found::Label(record) => check_duplicate(name, record),
+            Found::Alias(alias) => use_alias(Some(name), alias),
+        }
+    }
+
+    fn find_static(domain: DomainKey, id: LabelId) -> Option<Found> {
+        REGISTRY.read().table(domain)?.find_static(id)
+    }
+
+    pub fn intern(domain: DomainKey, id: LabelId, name: &'static str) -> Result<Record, Cow<'static, str>> {
+        let found = match find_static(domain, id) {
+            Some(found) => found,
+            None => REGISTRY.write().table_mut(domain).intern(id, name, || name)?,
+        };
+        resolve(name, found)
+    }
+
+    pub fn intern_owned(domain: DomainKey, id: LabelId, name: &str) -> Result<Record, Cow<'static, str>> {
+        let found = match find_static(domain, id) {
+            Some(found) => found,
+            None => REGISTRY.write().table_mut(domain).intern(id, name, || Box::leak(name.into()))?,
+        };
+        resolve(name, found)
+    }
+
+    /// The live name of the id, or the alias of an id without one.
+    fn lookup(domain: DomainKey, id: LabelId) -> Option<Result<Shared, LabelAlias>> {
+        let registry = REGISTRY.read();
+        let table = registry.table(domain)?;
+        if let Some(slot) = table.slots.get(&id) {
+            match &slot.name {
+                Name::Static(name) => return Some(Ok(Shared::Static(Record { id, index: slot.index, name }))),
+                Name::Reclaimable { entry, .. } => {
+                    if let Some(entry) = entry.upgrade() {
+                        return Some(Ok(Shared::Reclaimable(entry)));
+                    }
+                }
+            }
+        }
+        table.aliases.get(&id).copied().map(Err)
+    }
+
+    pub fn intern_shared(domain: DomainKey, id: LabelId, name: &str) -> Result<Shared, Cow<'static, str>> {
+        match lookup(domain, id) {
+            Some(Ok(Shared::Static(record))) => return check_duplicate(name, record).map(Shared::Static),
+            Some(Ok(Shared::Reclaimable(entry))) if &*entry.name == name => return Ok(Shared::Reclaimable(entry)),
+            Some(Ok(Shared::Reclaimable(entry))) => return Err(Cow::Owned(entry.name.to_string())),
+            Some(Err(alias)) => return use_alias(Some(name), alias).map(Shared::Static),
+            None => {}
+        }
+
+        let mut registry = REGISTRY.write();
+        let table = registry.table_mut(domain);
+        if let Some(slot) = table.slots.get(&id) {
+            if let Some(record) = DomainTable::record(id, slot) {
+                return check_duplicate(name, record).map(Shared::Static);
+            }
+            if let Some(existing) = slot.name() {
+                // Only upgrade once the names are known to match, so that no entry is dropped while locked.
+                if existing != name {
+                    return Err(Cow::Owned(existing.to_owned()));
+                }
+                if let Name::Reclaimable { entry, .. } = &slot.name {
+                    if let Some(entry) = entry.upgrade() {
+                        return Ok(Shared::Reclaimable(entry));
+                    }
+                }
+            }
+        }
+        if let Some(alias) = table.aliases.get(&id).copied() {
+            drop(registry);
+            return use_alias(Some(name), alias).map(Shared::Static);
+        }
+        let name: Arc<str> = name.into();
+        let entry = Arc::new(SharedEntry { domain, id, index: table.index(id), name: name.clone() });
+        table.set_name(id, Name::Reclaimable { name, entry: Arc::downgrade(&entry) });
+        Ok(Shared::Reclaimable(entry))
+    }
+
+    /// Unregisters the name of an entry that is being dropped, unless it was replaced in the meantime.
+    pub fn release(entry: &SharedEntry) {
+        let mut registry = REGISTRY.write();
+        let table = registry.table_mut(entry.domain);
+        let Some(slot) = table.slots.get(&entry.id) else { return };
+        if matches!(&slot.name, Name::Reclaimable { entry: current, .. } if core::ptr::eq(current.as_ptr(), entry)) {
+            table.vacate(entry.id);
+        }
+    }
+
+    pub fn get_shared(domain: DomainKey, id: LabelId, name: Option<&str>) -> Option<Shared> {
+        match lookup(domain, id)? {
+            Ok(Shared::Static(record)) if name.is_some_and(|name| name != record.name) => None,
+            Ok(Shared::Reclaimable(entry)) if name.is_some_and(|name| name != &*entry.name) => None,
+            Ok(shared) => Some(shared),
+            Err(alias) => use_alias(name, alias).ok().map(Shared::Static),
+        }
+    }
+
+    pub fn id_by_index(domain: DomainKey, index: u32) -> Option<LabelId> {
+        REGISTRY.read().table(domain)?.order.get(index as usize).copied().flatten()
+    }
+
+    pub fn entries(domain: DomainKey) -> Vec<Record> {
+        match REGISTRY.read().table(domain) {
+            Some(table) => {
+                table.order.iter().flatten().filter_map(|id| DomainTable::record(*id, &table.slots[id])).collect()
+            }
+            None => Vec::new(),
+        }
+    }
+
+    pub fn ids(domain: DomainKey) -> Vec<LabelId> {
+        REGISTRY.read().table(domain).map(|table| table.order.iter().flatten().copied().collect()).unwrap_or_default()
+    }
+
+    pub fn stats(domain: DomainKey) -> DynLabelStats {
+        REGISTRY.read().table(domain).map(|table| table.stats).unwrap_or_default()
+    }
+
+    pub fn add_alias(
+        domain: DomainKey,
+        id: LabelId,
+        name: &str,
+        target: Record,
+    ) -> Result<LabelAlias, LabelAliasError> {
+        let mut registry = REGISTRY.write();
+        let table = registry.table_mut(domain);
+        let collision = |existing: Cow<'static, str>| {
+            LabelAliasError::Collision(LabelCollision { id, name: name.to_owned(), existing })
+        };
+        if let Some(existing) = table.slots.get(&id).and_then(Slot::name) {
+            return Err(if existing == name {
+                LabelAliasError::Registered
+            } else {
+                collision(Cow::Owned(existing.to_owned()))
+            });
+        }
+        if let Some(alias) = table.aliases.get(&id) {
+            return match alias {
+                alias if alias.name != name => Err(collision(Cow::Borrowed(alias.name))),
+                alias if alias.target != target.id => Err(LabelAliasError::Aliased { target: alias.target_name }),
+                alias => Ok(*alias),
+            };
+        }
+        let alias = LabelAlias::new(domain.name, id, Box::leak(name.into()), target);
+        table.aliases.insert(id, alias);
+        Ok(alias)
+    }
+
+    pub fn aliases(domain: DomainKey) -> Vec<LabelAlias> {
+        let mut aliases: Vec<_> = match REGISTRY.read().table(domain) {
+            Some(table) => table.aliases.values().copied().collect(),
+            None => Vec::new(),
+        };
+        aliases.sort_unstable_by_key(|alias| alias.name);
+        aliases
+    }
+
+    pub fn set_alias_hook(hook: Option<LabelAliasHook>) {
+        *ALIAS_HOOK.write() = hook;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn install() {
+        static OTHER_BUILD: Operations = Operations { abi: !ABI, ..LOCAL_OPERATIONS };
+
+        assert_eq!(install_label_registry(LabelRegistry::current()), Ok(()));
+        assert_eq!(install_label_registry(LabelRegistry(&OTHER_BUILD)), Err(InstallRegistryError::AbiMismatch));
+        assert_eq!(LabelRegistry::current(), LabelRegistry(&LOCAL));
+    }
+
+    #[test]
+    fn domain_names() {
+        use crate::label::{Label, LabelDomain};
+
+        struct Named;
+        impl LabelDomain for Named {
+            const NAME: &'static str = "xaoc_utils::label::registry::tests::Named";
+        }
+        struct SameName;
+        impl LabelDomain for SameName {
+            const NAME: &'static str = Named::NAME;
+        }
+
+        // Labels registered by another copy of the crate first, known by name only.
+        let foreign = DomainKey { type_id: None, ..domain::<Named>() };
+        let from_plugin = intern(foreign, 1, "from_plugin").unwrap();
+        assert_eq!(Label::<Named>::from_id(1).map(|label| label.id()), Some(from_plugin.id));
+        let host = Label::<Named>::new("from_host");
+        assert_eq!(
+            entries(foreign).into_iter().map(|record| record.name).collect::<Vec<_>>(),
+            [from_plugin.name, host.name()]
+        );
+
+        assert_eq!(Label::<SameName>::from_id(1), None);
+        assert!(std::panic::catch_unwind(|| Label::<SameName>::new("from_host")).is_err());
+        assert_eq!(Label::<Named>::registered(), [Label::<Named>::from_id(1).unwrap(), host]);
+    }
+}
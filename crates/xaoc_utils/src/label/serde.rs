@@ -0,0 +1,128 @@
+use super::{ConstLabel, DynLabel, Label, LabelDomain};
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use core::fmt::Formatter;
+use core::marker::PhantomData;
+use serde::de::{Error, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+impl<Domain> Serialize for Label<Domain> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name)
+    }
+}
+
+impl<Domain> Serialize for ConstLabel<Domain> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name)
+    }
+}
+
+impl<Domain> Serialize for DynLabel<Domain> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+/// Re-interns a label by name, failing instead of panicking if its id collides with a registered name.
+struct LabelVisitor<Domain>(PhantomData<Domain>);
+
+impl<'de, Domain: LabelDomain> Visitor<'de> for LabelVisitor<Domain> {
+    type Value = Label<Domain>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> core::fmt::Result {
+        write!(formatter, "a label name of domain {}", core::any::type_name::<Domain>())
+    }
+
+    fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+        Label::try_new(v.to_owned()).map_err(E::custom)
+    }
+
+    fn visit_string<E: Error>(self, v: String) -> Result<Self::Value, E> {
+        Label::try_new(v).map_err(E::custom)
+    }
+}
+
+impl<'de, Domain: LabelDomain> Deserialize<'de> for Label<Domain> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(LabelVisitor(PhantomData))
+    }
+}
+
+impl<'de, Domain: LabelDomain> Deserialize<'de> for DynLabel<Domain> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = alloc::borrow::Cow::<str>::deserialize(deserializer)?;
+        DynLabel::try_new(&name).map_err(D::Error::custom)
+    }
+}
+
+impl<'de, Domain: LabelDomain> Deserialize<'de> for ConstLabel<Domain> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let label = Label::<Domain>::deserialize(deserializer)?;
+        Ok(ConstLabel { id: label.id, name: label.name, _marker: PhantomData })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::{HashMap, StableHashMap};
+
+    struct Tag;
+    impl LabelDomain for Tag {
+        const NAME: &'static str = "xaoc_utils::label::serde::tests::Tag";
+    }
+
+    #[test]
+    fn label_round_trip() {
+        const L1: ConstLabel<Tag> = ConstLabel::new("serde_1");
+
+        let json = serde_json::to_string(&L1.label()).unwrap();
+        assert_eq!(json, "\"serde_1\"");
+        assert_eq!(serde_json::from_str::<Label<Tag>>(&json).unwrap(), L1.label());
+        assert_eq!(serde_json::to_string(&L1).unwrap(), json);
+        assert_eq!(serde_json::from_str::<ConstLabel<Tag>>(&json).unwrap().id, L1.id);
+
+        let fresh: Label<Tag> = serde_json::from_str("\"serde_2\"").unwrap();
+        assert_eq!(Label::<Tag>::lookup("serde_2"), Some(fresh));
+
+        let dynamic: DynLabel<Tag> = serde_json::from_str("\"serde_3\"").unwrap();
+        assert!(dynamic.is_reclaimable());
+        assert_eq!(serde_json::to_string(&dynamic).unwrap(), "\"serde_3\"");
+    }
+
+    #[test]
+    fn label_keyed_maps() {
+        let mut map = StableHashMap::default();
+        map.insert(Label::<Tag>::new("serde_a"), 1);
+        map.insert(Label::<Tag>::new("serde_b"), 2);
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(serde_json::from_str::<StableHashMap<Label<Tag>, i32>>(&json).unwrap(), map);
+        assert_eq!(serde_json::from_str::<HashMap<Label<Tag>, i32>>(&json).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn renamed_label() {
+        struct Renamed;
+        impl LabelDomain for Renamed {
+            const NAME: &'static str = "xaoc_utils::label::serde::tests::renamed_label::Renamed";
+        }
+        let label = Label::<Renamed>::new("serde/renamed");
+        label.add_alias("serde_old").unwrap();
+        assert_eq!(serde_json::from_str::<Label<Renamed>>("\"serde_old\"").unwrap(), label);
+        assert_eq!(serde_json::from_str::<DynLabel<Renamed>>("\"serde_old\"").unwrap(), label);
+        assert_eq!(serde_json::to_string(&label).unwrap(), "\"serde/renamed\"");
+    }
+
+    #[test]
+    fn label_collision_is_an_error() {
+        struct Collision;
+        impl LabelDomain for Collision {
+            const NAME: &'static str = "xaoc_utils::label::serde::tests::label_collision_is_an_error::Collision";
+        }
+        let id = ConstLabel::<Collision>::new("serde_collision").id();
+        crate::label::registry::intern(crate::label::registry::domain::<Collision>(), id, "serde_other").unwrap();
+        let error = serde_json::from_str::<Label<Collision>>("\"serde_collision\"").unwrap_err();
+        assert!(error.to_string().contains("Duplicate hash value"));
+    }
+}
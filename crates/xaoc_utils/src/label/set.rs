@@ -0,0 +1,392 @@
+use super::{Label, LabelDomain};
+use crate::hash::PassHash;
+use alloc::vec::Vec;
+use core::fmt::{Debug, Formatter};
+use core::marker::PhantomData;
+use core::ops::{Index, IndexMut};
+
+/// A [`HashMap`][hashbrown::HashMap] keyed by labels, hashing them by their pre-computed id with [`PassHash`].
+/// Prefer [`LabelMap`] unless the keys are few and sparse among the labels registered in the domain.
+pub type LabelHashMap<Domain, V> = hashbrown::HashMap<Label<Domain>, V, PassHash>;
+
+/// A [`HashSet`][hashbrown::HashSet] of labels, hashing them by their pre-computed id with [`PassHash`].
+/// Prefer [`LabelSet`] unless the labels are few and sparse among the labels registered in the domain.
+pub type LabelHashSet<Domain> = hashbrown::HashSet<Label<Domain>, PassHash>;
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// A set of labels stored as a bitset indexed by [`Label::index`], with O(1) membership tests.
+/// Its size grows with the largest index inserted, not with the number of labels.
+pub struct LabelSet<Domain> {
+    words: Vec<u64>,
+    _marker: PhantomData<Domain>,
+}
+
+impl<Domain> LabelSet<Domain> {
+    pub fn new() -> Self {
+        Self { words: Vec::new(), _marker: PhantomData }
+    }
+
+    /// Adds the label, returning `true` if it was not in the set before.
+    pub fn insert(&mut self, label: Label<Domain>) -> bool {
+        let (word, bit) = Self::position(label.index);
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+        let absent = self.words[word] & bit == 0;
+        self.words[word] |= bit;
+        absent
+    }
+
+    /// Removes the label, returning `true` if it was in the set.
+    pub fn remove(&mut self, label: Label<Domain>) -> bool {
+        let (word, bit) = Self::position(label.index);
+        match self.words.get_mut(word) {
+            Some(word) => {
+                let present = *word & bit != 0;
+                *word &= !bit;
+                present
+            }
+            None => false,
+        }
+    }
+
+    #[inline]
+    pub fn contains(&self, label: Label<Domain>) -> bool {
+        self.contains_index(label.index)
+    }
+
+    #[inline]
+    pub fn contains_index(&self, index: u32) -> bool {
+        let (word, bit) = Self::position(index);
+        self.words.get(word).is_some_and(|word| word & bit != 0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|word| *word == 0)
+    }
+
+    pub fn clear(&mut self) {
+        self.words.clear();
+    }
+
+    /// Iterates the dense indices of the labels in the set, in ascending order.
+    pub fn indices(&self) -> impl Iterator<Item = u32> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, word)| {
+            let mut word = *word;
+            core::iter::from_fn(move || {
+                (word != 0).then(|| {
+                    let bit = word.trailing_zeros() as usize;
+                    word &= word - 1;
+                    (i * WORD_BITS + bit) as u32
+                })
+            })
+        })
+    }
+
+    pub fn union_with(&mut self, other: &Self) {
+        if self.words.len() < other.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+        self.words.iter_mut().zip(&other.words).for_each(|(word, other)| *word |= other);
+    }
+
+    pub fn intersect_with(&mut self, other: &Self) {
+        self.words.truncate(other.words.len());
+        self.words.iter_mut().zip(&other.words).for_each(|(word, other)| *word &= other);
+    }
+
+    pub fn difference_with(&mut self, other: &Self) {
+        self.words.iter_mut().zip(&other.words).for_each(|(word, other)| *word &= !other);
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.words.iter().enumerate().all(|(i, word)| word & !other.words.get(i).copied().unwrap_or(0) == 0)
+    }
+
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.words.iter().zip(&other.words).all(|(word, other)| word & other == 0)
+    }
+
+    #[inline]
+    fn position(index: u32) -> (usize, u64) {
+        let index = index as usize;
+        (index / WORD_BITS, 1 << (index % WORD_BITS))
+    }
+}
+
+impl<Domain: LabelDomain> LabelSet<Domain> {
+    /// Iterates the labels in the set, in registration order.
+    pub fn iter(&self) -> impl Iterator<Item = Label<Domain>> + '_ {
+        self.indices().filter_map(Label::from_index)
+    }
+}
+
+impl<Domain> Default for LabelSet<Domain> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Domain> Clone for LabelSet<Domain> {
+    fn clone(&self) -> Self {
+        Self { words: self.words.clone(), _marker: PhantomData }
+    }
+}
+
+impl<Domain> PartialEq for LabelSet<Domain> {
+    fn eq(&self, other: &Self) -> bool {
+        let (short, long) = if self.words.len() <= other.words.len() { (self, other) } else { (other, self) };
+        short.words == long.words[..short.words.len()] && long.words[short.words.len()..].iter().all(|word| *word == 0)
+    }
+}
+
+impl<Domain> Eq for LabelSet<Domain> {}
+
+impl<Domain: LabelDomain> Debug for LabelSet<Domain> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<Domain> Extend<Label<Domain>> for LabelSet<Domain> {
+    fn extend<T: IntoIterator<Item = Label<Domain>>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|label| {
+            self.insert(label);
+        });
+    }
+}
+
+impl<Domain> FromIterator<Label<Domain>> for LabelSet<Domain> {
+    fn from_iter<T: IntoIterator<Item = Label<Domain>>>(iter: T) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+/// A map keyed by labels stored as a vec indexed by [`Label::index`], with O(1) lookups.
+/// Its size grows with the largest index inserted, not with the number of entries.
+pub struct LabelMap<Domain, V> {
+    slots: Vec<Option<(Label<Domain>, V)>>,
+    len: usize,
+}
+
+impl<Domain, V> LabelMap<Domain, V> {
+    pub fn new() -> Self {
+        Self { slots: Vec::new(), len: 0 }
+    }
+
+    /// Inserts the value, returning the previous value of the label if any.
+    pub fn insert(&mut self, label: Label<Domain>, value: V) -> Option<V> {
+        let index = label.index as usize;
+        if index >= self.slots.len() {
+            self.slots.resize_with(index + 1, || None);
+        }
+        let previous = self.slots[index].replace((label, value)).map(|(_, value)| value);
+        if previous.is_none() {
+            self.len += 1;
+        }
+        previous
+    }
+
+    /// Returns the value of the label, inserting the value returned by `func` if there is none.
+    pub fn get_or_insert_with<F: FnOnce() -> V>(&mut self, label: Label<Domain>, func: F) -> &mut V {
+        if !self.contains_key(label) {
+            self.insert(label, func());
+        }
+        &mut self.slots[label.index as usize].as_mut().unwrap().1
+    }
+
+    pub fn remove(&mut self, label: Label<Domain>) -> Option<V> {
+        let removed = self.slots.get_mut(label.index as usize)?.take().map(|(_, value)| value);
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    #[inline]
+    pub fn get(&self, label: Label<Domain>) -> Option<&V> {
+        self.slots.get(label.index as usize)?.as_ref().map(|(_, value)| value)
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, label: Label<Domain>) -> Option<&mut V> {
+        self.slots.get_mut(label.index as usize)?.as_mut().map(|(_, value)| value)
+    }
+
+    #[inline]
+    pub fn contains_key(&self, label: Label<Domain>) -> bool {
+        self.get(label).is_some()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.len = 0;
+    }
+
+    /// Iterates the entries in registration order of their labels.
+    pub fn iter(&self) -> impl Iterator<Item = (Label<Domain>, &V)> {
+        self.slots.iter().flatten().map(|(label, value)| (*label, value))
+    }
+
+    /// Iterates the entries in registration order of their labels.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Label<Domain>, &mut V)> {
+        self.slots.iter_mut().flatten().map(|(label, value)| (*label, value))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = Label<Domain>> + '_ {
+        self.iter().map(|(label, _)| label)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, value)| value)
+    }
+
+    /// Returns the set of labels that have a value.
+    pub fn key_set(&self) -> LabelSet<Domain> {
+        self.keys().collect()
+    }
+}
+
+impl<Domain, V> Default for LabelMap<Domain, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Domain, V: Clone> Clone for LabelMap<Domain, V> {
+    fn clone(&self) -> Self {
+        Self { slots: self.slots.clone(), len: self.len }
+    }
+}
+
+impl<Domain, V: PartialEq> PartialEq for LabelMap<Domain, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().all(|(label, value)| other.get(label) == Some(value))
+    }
+}
+
+impl<Domain, V: Eq> Eq for LabelMap<Domain, V> {}
+
+impl<Domain, V: Debug> Debug for LabelMap<Domain, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<Domain, V> Index<Label<Domain>> for LabelMap<Domain, V> {
+    type Output = V;
+
+    fn index(&self, label: Label<Domain>) -> &V {
+        self.get(label).unwrap_or_else(|| panic!("{:?} is not in the map", label))
+    }
+}
+
+impl<Domain, V> IndexMut<Label<Domain>> for LabelMap<Domain, V> {
+    fn index_mut(&mut self, label: Label<Domain>) -> &mut V {
+        self.get_mut(label).unwrap_or_else(|| panic!("{:?} is not in the map", label))
+    }
+}
+
+impl<Domain, V> Extend<(Label<Domain>, V)> for LabelMap<Domain, V> {
+    fn extend<T: IntoIterator<Item = (Label<Domain>, V)>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|(label, value)| {
+            self.insert(label, value);
+        });
+    }
+}
+
+impl<Domain, V> FromIterator<(Label<Domain>, V)> for LabelMap<Domain, V> {
+    fn from_iter<T: IntoIterator<Item = (Label<Domain>, V)>>(iter: T) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<Domain, V> IntoIterator for LabelMap<Domain, V> {
+    type Item = (Label<Domain>, V);
+    type IntoIter = core::iter::Flatten<alloc::vec::IntoIter<Option<(Label<Domain>, V)>>>;
+
+    /// Consumes the map, e.g. to collect it into a [`LabelHashMap`].
+    fn into_iter(self) -> Self::IntoIter {
+        self.slots.into_iter().flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Tag;
+    impl LabelDomain for Tag {
+        const NAME: &'static str = "xaoc_utils::label::set::tests::Tag";
+    }
+
+    #[test]
+    fn label_set() {
+        let labels: Vec<_> = (0..100).map(|i| Label::<Tag>::new(format!("set_{}", i))).collect();
+        let mut evens: LabelSet<Tag> = labels.iter().step_by(2).copied().collect();
+        let mut all: LabelSet<Tag> = labels.iter().copied().collect();
+
+        assert_eq!(evens.len(), 50);
+        assert!(evens.contains(labels[98]));
+        assert!(!evens.contains(labels[99]));
+        assert!(evens.is_subset(&all));
+        assert!(!all.is_subset(&evens));
+        assert_eq!(evens.iter().collect::<Vec<_>>(), labels.iter().step_by(2).copied().collect::<Vec<_>>());
+
+        all.difference_with(&evens);
+        assert!(all.is_disjoint(&evens));
+        assert!(!evens.insert(labels[0]));
+        assert!(evens.remove(labels[0]));
+        assert!(!evens.remove(labels[0]));
+        evens.intersect_with(&all);
+        assert!(evens.is_empty());
+        assert_eq!(evens, LabelSet::new());
+
+        let sparse: LabelHashSet<Tag> = all.iter().collect();
+        assert_eq!(sparse.len(), 50);
+        assert_eq!(sparse.into_iter().collect::<LabelSet<Tag>>(), all);
+    }
+
+    #[test]
+    fn label_map() {
+        let a = Label::<Tag>::new("map_a");
+        let b = Label::<Tag>::new("map_b");
+        let mut map = LabelMap::new();
+        assert_eq!(map.insert(b, 2), None);
+        assert_eq!(map.insert(a, 1), None);
+        assert_eq!(map.insert(a, 3), Some(1));
+        assert_eq!(map.len(), 2);
+        assert_eq!(map[a], 3);
+        *map.get_or_insert_with(b, || 0) += 1;
+        assert_eq!(map.get(b), Some(&3));
+        assert_eq!(map.keys().collect::<Vec<_>>(), [a, b]);
+
+        let sparse: LabelHashMap<Tag, i32> = map.clone().into_iter().collect();
+        assert_eq!(sparse[&b], 3);
+        assert_eq!(sparse.into_iter().collect::<LabelMap<_, _>>(), map);
+
+        assert_eq!(map.remove(a), Some(3));
+        assert_eq!(map.remove(a), None);
+        assert!(!map.contains_key(a));
+        assert_eq!(map.len(), 1);
+    }
+}
@@ -1,9 +1,25 @@
+//! Utilities shared by the xaoc crates.
+//!
+//! Builds without the standard library when the default `std` feature is disabled, with `alloc`.
+//! The label registry and [`Interner`][intern::Interner] then lock with spin locks,
+//! and [`ConcurrentHashMap`][hash::ConcurrentHashMap] is unavailable.
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+// Lets the derives refer to this crate as `::xaoc_utils` from within it.
+extern crate self as xaoc_utils;
+
 pub mod prelude {
     pub use crate::default;
+    pub use xaoc_derive::XaocDefault;
 }
 
 mod default;
 pub use default::default;
 
 pub mod hash;
+pub mod intern;
 pub mod label;
+#[cfg(feature = "settings")]
+pub mod settings;
+mod sync;
@@ -0,0 +1,617 @@
+//! Typed settings resolved in layers: the compiled-in [`default()`][crate::default], then a TOML file,
+//! then `XAOC_*` environment variables, then command-line overrides, each overriding the fields set by the
+//! previous ones, with a [`SettingsReport`] of the layer each field came from.
+//!
+//! Each settings struct is identified by a [`ConstLabel<SettingsDomain>`], whose name is its table in the
+//! TOML file, with the `/`-separated segments of the name as nested tables. For a `window` label:
+//! - the file sets `width` in a `[window]` table,
+//! - the environment sets it with `XAOC_WINDOW__WIDTH=1920`, with `__` between the segments,
+//! - the command line sets it with `--set window.width=1920`.
+//!
+//! Environment and command-line values are parsed as TOML values when the default of the field they set is
+//! not a string, falling back to strings, so `XAOC_WINDOW__TITLE=demo` needs no quotes and
+//! `XAOC_WINDOW__TITLE=2024` stays a string. Values of fields missing from the serialized defaults are
+//! kept as strings.
+//!
+//! The segments of variable names are matched against the label and the serialized field names ignoring
+//! ASCII case, with `_` also matching `-`, so that a field renamed to `maxFps` by serde is set with
+//! `XAOC_WINDOW__MAXFPS` and one renamed to `frame-limit` with `XAOC_WINDOW__FRAME_LIMIT`. Fields missing
+//! from the serialized defaults, such as options that are `None`, can only be matched by their lowercase name.
+//! [`SETTINGS_ENV`] names the settings file and is not an override.
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use xaoc_utils::label::ConstLabel;
+//! use xaoc_utils::prelude::*;
+//! use xaoc_utils::settings::{Settings, SettingsDomain, SettingsLayer, SettingsSources};
+//!
+//! #[derive(XaocDefault, Serialize, Deserialize)]
+//! struct Window {
+//!     #[default = 1280]
+//!     width: u32,
+//!     #[default = 720]
+//!     height: u32,
+//!     vsync: bool,
+//! }
+//!
+//! impl Settings for Window {
+//!     const LABEL: ConstLabel<SettingsDomain> = ConstLabel::new("window");
+//! }
+//!
+//! let sources = SettingsSources::new()
+//!     .with_toml("[window]\nheight = 1080", "xaoc.toml")?
+//!     .with_env([("XAOC_WINDOW__VSYNC", "true")])
+//!     .with_args(["--set", "window.height=1440"])?;
+//! let (window, report) = sources.resolve::<Window>()?;
+//! assert_eq!((window.width, window.height, window.vsync), (1280, 1440, true));
+//! assert_eq!(report.layer("height"), Some(SettingsLayer::CommandLine));
+//! assert_eq!(report.layer("width"), Some(SettingsLayer::Default));
+//! # Ok::<(), xaoc_utils::settings::SettingsError>(())
+//! ```
+
+use crate::hash::StableIndexMap;
+use crate::label::{ConstLabel, Label, LabelDomain, LabelHashMap, SEPARATOR};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::any::Any;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+/// The prefix of the environment variables overriding settings.
+pub const ENV_PREFIX: &str = "XAOC_";
+
+/// The environment variable giving the path of the settings file, see [`SettingsSources::from_process`].
+pub const SETTINGS_ENV: &str = "XAOC_SETTINGS";
+
+/// The label domain of settings structs.
+pub struct SettingsDomain;
+
+impl LabelDomain for SettingsDomain {
+    const NAME: &'static str = "xaoc_utils::settings";
+}
+
+/// A settings struct, resolved by [`SettingsSources::resolve`] from its [`Default`] and the sources.
+pub trait Settings: Default + Serialize + DeserializeOwned + Send + Sync + 'static {
+    /// Identifies the settings, its name is their table in the sources, see the [module documentation][self].
+    const LABEL: ConstLabel<SettingsDomain>;
+}
+
+/// The layer a setting came from, in the order in which they override each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SettingsLayer {
+    Default,
+    File,
+    Environment,
+    CommandLine,
+}
+
+impl Display for SettingsLayer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Default => "default",
+            Self::File => "file",
+            Self::Environment => "environment",
+            Self::CommandLine => "command line",
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum SettingsError {
+    Io {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+    /// The settings file is not valid TOML.
+    Toml {
+        path: PathBuf,
+        error: Box<toml::de::Error>,
+    },
+    /// A command-line argument is not of the form `--set table.field=value`.
+    InvalidArgument(String),
+    /// The [`Default`] of the settings does not serialize into a TOML table.
+    Serialize {
+        label: Label<SettingsDomain>,
+        error: Box<toml::ser::Error>,
+    },
+    /// The layered values do not deserialize into the settings, e.g. because an override has the wrong type.
+    Deserialize {
+        label: Label<SettingsDomain>,
+        error: Box<toml::de::Error>,
+    },
+    /// Other settings were registered with the label of the settings of this type.
+    LabelConflict {
+        label: Label<SettingsDomain>,
+        settings: &'static str,
+    },
+}
+
+impl Display for SettingsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io { path, error } => write!(f, "cannot read settings file {}: {error}", path.display()),
+            Self::Toml { path, error } => write!(f, "invalid settings file {}: {error}", path.display()),
+            Self::InvalidArgument(arg) => write!(f, "invalid settings argument {arg:?}, expected `table.field=value`"),
+            Self::Serialize { label, error } => {
+                write!(f, "cannot serialize the default {} settings: {error}", label.name())
+            }
+            Self::Deserialize { label, error } => write!(f, "invalid {} settings: {error}", label.name()),
+            Self::LabelConflict { label, settings } => {
+                write!(f, "settings {settings} have the label {} of other registered settings", label.name())
+            }
+        }
+    }
+}
+
+impl std::error::Error for SettingsError {}
+
+/// The layers above the defaults, to resolve settings from.
+#[derive(Debug, Clone, Default)]
+pub struct SettingsSources {
+    file: Table,
+    file_path: Option<PathBuf>,
+    /// `(segments, value)` of the `XAOC_*` variables, with the segments as named.
+    env: Vec<(Vec<String>, String)>,
+    /// `(segments, value)` of the `--set` arguments.
+    args: Vec<(Vec<String>, String)>,
+}
+
+impl SettingsSources {
+    /// Sources without any layer above the defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The sources of the process: the `XAOC_*` environment variables and the command-line arguments,
+    /// with the file given by `--settings <path>`, or else by the [`SETTINGS_ENV`] variable.
+    pub fn from_process() -> Result<Self, SettingsError> {
+        let sources = Self::new();
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        let sources = match std::env::var_os(SETTINGS_ENV) {
+            Some(path) if !args.iter().any(|arg| arg == "--settings" || arg.starts_with("--settings=")) => {
+                sources.with_file(path)?
+            }
+            _ => sources,
+        };
+        sources.with_env(std::env::vars()).with_args(args)
+    }
+
+    /// Reads the settings file, replacing any file read before.
+    pub fn with_file(self, path: impl AsRef<Path>) -> Result<Self, SettingsError> {
+        let path = path.as_ref();
+        let toml = std::fs::read_to_string(path).map_err(|error| SettingsError::Io { path: path.into(), error })?;
+        self.with_toml(&toml, path)
+    }
+
+    /// Parses the contents of a settings file read from `path`, replacing any file read before.
+    pub fn with_toml(mut self, toml: &str, path: impl AsRef<Path>) -> Result<Self, SettingsError> {
+        let path = path.as_ref();
+        self.file = toml.parse().map_err(|error| SettingsError::Toml { path: path.into(), error: Box::new(error) })?;
+        self.file_path = Some(path.into());
+        Ok(self)
+    }
+
+    /// Adds the variables starting with [`ENV_PREFIX`], other variables and [`SETTINGS_ENV`] are ignored.
+    pub fn with_env<I, K, V>(mut self, vars: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        for (name, value) in vars {
+            let name = name.as_ref();
+            let Some(name) = name.strip_prefix(ENV_PREFIX).filter(|_| name != SETTINGS_ENV) else {
+                continue;
+            };
+            let segments = name.split("__").map(String::from).collect();
+            self.env.push((segments, value.as_ref().to_string()));
+        }
+        self
+    }
+
+    /// Adds the `--set table.field=value` arguments, and reads the file of a `--settings <path>` argument.
+    /// Both also accept the `--set=table.field=value` form. Other arguments are ignored.
+    pub fn with_args<I: IntoIterator<Item = S>, S: AsRef<str>>(mut self, args: I) -> Result<Self, SettingsError> {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let arg = arg.as_ref();
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+                _ => (arg, None),
+            };
+            if flag != "--set" && flag != "--settings" {
+                continue;
+            }
+            let Some(value) = inline.or_else(|| args.next().map(|arg| arg.as_ref().to_string())) else {
+                return Err(SettingsError::InvalidArgument(arg.to_string()));
+            };
+            if flag == "--settings" {
+                self = self.with_file(value)?;
+                continue;
+            }
+            match value.split_once('=') {
+                Some((path, value)) if !path.is_empty() => {
+                    self.args.push((path.split('.').map(String::from).collect(), value.to_string()));
+                }
+                _ => return Err(SettingsError::InvalidArgument(value)),
+            }
+        }
+        Ok(self)
+    }
+
+    /// Resolves the settings `T` from its [`Default`] and the layers of the sources.
+    pub fn resolve<T: Settings>(&self) -> Result<(T, SettingsReport), SettingsError> {
+        let label = T::LABEL.label();
+        let mut report = SettingsReport { label, file: self.file_path.clone(), fields: StableIndexMap::default() };
+        let mut table = match Value::try_from(T::default()) {
+            Ok(Value::Table(table)) => table,
+            Ok(_) => {
+                return Err(SettingsError::Serialize {
+                    label,
+                    error: Box::new(serde::ser::Error::custom("not a table")),
+                })
+            }
+            Err(error) => return Err(SettingsError::Serialize { label, error: Box::new(error) }),
+        };
+        report.mark(&mut Vec::new(), &Value::Table(table.clone()), SettingsLayer::Default);
+
+        let prefix: Vec<String> = label.name().split(SEPARATOR).map(String::from).collect();
+        let mut file = Some(&self.file);
+        for segment in &prefix {
+            file = file.and_then(|table| table.get(segment)).and_then(Value::as_table);
+        }
+        if let Some(file) = file {
+            merge(&mut table, file, &mut Vec::new(), SettingsLayer::File, &mut report);
+        }
+        for (layer, overrides) in [(SettingsLayer::Environment, &self.env), (SettingsLayer::CommandLine, &self.args)] {
+            for (segments, value) in overrides {
+                let path = match layer {
+                    SettingsLayer::Environment => env_path(segments, &prefix, &table),
+                    _ => segments.strip_prefix(prefix.as_slice()).map(<[String]>::to_vec),
+                };
+                let Some(path) = path.filter(|path| !path.is_empty()) else {
+                    continue;
+                };
+                let mut source = parse_value(value, get_path(&table, &path));
+                for segment in path.iter().rev() {
+                    source = Value::Table(Table::from_iter([(segment.clone(), source)]));
+                }
+                let Value::Table(source) = source else { unreachable!() };
+                merge(&mut table, &source, &mut Vec::new(), layer, &mut report);
+            }
+        }
+
+        let value = T::deserialize(Value::Table(table))
+            .map_err(|error| SettingsError::Deserialize { label, error: Box::new(error) })?;
+        Ok((value, report))
+    }
+}
+
+/// Whether the segment of a variable name matches a label segment or a field name, see the
+/// [module documentation][self].
+fn env_matches(segment: &str, name: &str) -> bool {
+    segment.len() == name.len()
+        && segment.bytes().zip(name.bytes()).all(|(s, n)| s.eq_ignore_ascii_case(&n) || (s == b'_' && n == b'-'))
+}
+
+/// The path within the settings of the segments of a variable name, if they start with the `prefix` of the
+/// label, with each segment replaced by the matching field name of `table`, or else lowercased.
+fn env_path(segments: &[String], prefix: &[String], table: &Table) -> Option<Vec<String>> {
+    if segments.len() < prefix.len() || !segments.iter().zip(prefix).all(|(segment, name)| env_matches(segment, name)) {
+        return None;
+    }
+    let mut table = Some(table);
+    let path = segments[prefix.len()..].iter().map(|segment| {
+        let key = table.and_then(|table| table.keys().find(|key| env_matches(segment, key)));
+        let key = key.cloned().unwrap_or_else(|| segment.to_lowercase());
+        table = table.and_then(|table| table.get(&key)).and_then(Value::as_table);
+        key
+    });
+    Some(path.collect())
+}
+
+/// The value at `path` within `table`, if any.
+fn get_path<'a>(table: &'a Table, path: &[String]) -> Option<&'a Value> {
+    let (last, tables) = path.split_last()?;
+    tables.iter().try_fold(table, |table, segment| table.get(segment)?.as_table())?.get(last)
+}
+
+/// Parses an environment or command-line value overriding `current` as a TOML value, or else as a string.
+/// The value stays a string if `current` is a string or unknown.
+fn parse_value(value: &str, current: Option<&Value>) -> Value {
+    if current.is_none_or(Value::is_str) {
+        return Value::String(value.to_string());
+    }
+    format!("value = {value}")
+        .parse::<Table>()
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| Value::String(value.to_string()))
+}
+
+/// Merges `source` into `target`, merging tables and replacing other values.
+fn merge(
+    target: &mut Table,
+    source: &Table,
+    path: &mut Vec<String>,
+    layer: SettingsLayer,
+    report: &mut SettingsReport,
+) {
+    for (key, value) in source {
+        path.push(key.clone());
+        match (target.get_mut(key), value) {
+            (Some(Value::Table(target)), Value::Table(source)) => merge(target, source, path, layer, report),
+            _ => {
+                report.mark(path, value, layer);
+                target.insert(key.clone(), value.clone());
+            }
+        }
+        path.pop();
+    }
+}
+
+/// The layer each field of a settings struct came from, see [`SettingsSources::resolve`].
+#[derive(Debug, Clone)]
+pub struct SettingsReport {
+    pub label: Label<SettingsDomain>,
+    /// The settings file of the sources, even if it had no table for these settings.
+    pub file: Option<PathBuf>,
+    /// The layer of each field, by its dotted path within the settings, in declaration order.
+    /// Fields of nested structs are listed individually, fields that are `None` by default are only listed
+    /// if a layer sets them.
+    pub fields: StableIndexMap<String, SettingsLayer>,
+}
+
+impl SettingsReport {
+    /// The layer the field with the dotted `path` came from.
+    pub fn layer(&self, path: &str) -> Option<SettingsLayer> {
+        self.fields.get(path).copied()
+    }
+
+    /// Records the layer of the value at `path`, or of each of its fields if it is a table.
+    fn mark(&mut self, path: &mut Vec<String>, value: &Value, layer: SettingsLayer) {
+        match value {
+            Value::Table(table) => {
+                for (key, value) in table {
+                    path.push(key.clone());
+                    self.mark(path, value, layer);
+                    path.pop();
+                }
+            }
+            _ => {
+                self.fields.insert(path.join("."), layer);
+            }
+        }
+    }
+}
+
+impl Display for SettingsReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}]", self.label.name())?;
+        for (path, layer) in &self.fields {
+            write!(f, "\n{path} = {layer}")?;
+            if let (SettingsLayer::File, Some(file)) = (layer, &self.file) {
+                write!(f, " ({})", file.display())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The settings of a program, each resolved once from the same [`SettingsSources`] when first registered.
+pub struct SettingsStore {
+    sources: SettingsSources,
+    values: LabelHashMap<SettingsDomain, Box<dyn Any + Send + Sync>>,
+    reports: Vec<SettingsReport>,
+}
+
+impl SettingsStore {
+    pub fn new(sources: SettingsSources) -> Self {
+        Self { sources, values: LabelHashMap::default(), reports: Vec::new() }
+    }
+
+    pub fn sources(&self) -> &SettingsSources {
+        &self.sources
+    }
+
+    /// Resolves the settings `T` if they were not registered before, and returns them.
+    /// Returns an error if other settings were registered with the same label.
+    pub fn register<T: Settings>(&mut self) -> Result<&T, SettingsError> {
+        let label = T::LABEL.label();
+        if !self.values.contains_key(&label) {
+            let (value, report) = self.sources.resolve::<T>()?;
+            self.values.insert(label, Box::new(value));
+            self.reports.push(report);
+        }
+        self.get::<T>().ok_or(SettingsError::LabelConflict { label, settings: std::any::type_name::<T>() })
+    }
+
+    /// Returns the settings `T` if they were registered.
+    pub fn get<T: Settings>(&self) -> Option<&T> {
+        self.values.get(&T::LABEL.label()).and_then(|value| value.downcast_ref())
+    }
+
+    /// The reports of the registered settings, in the order they were registered.
+    pub fn reports(&self) -> &[SettingsReport] {
+        &self.reports
+    }
+}
+
+impl Default for SettingsStore {
+    fn default() -> Self {
+        Self::new(SettingsSources::new())
+    }
+}
+
+impl Display for SettingsStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, report) in self.reports.iter().enumerate() {
+            if i > 0 {
+                f.write_str("\n\n")?;
+            }
+            Display::fmt(report, f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use serde::Deserialize;
+
+    #[derive(XaocDefault, Serialize, Deserialize, Debug, PartialEq)]
+    struct Shadows {
+        #[default = 2048]
+        resolution: u32,
+        cascades: Cascades,
+        filter: Option<String>,
+    }
+
+    #[derive(XaocDefault, Serialize, Deserialize, Debug, PartialEq)]
+    struct Cascades {
+        #[default = 4]
+        count: u8,
+        #[default = 0.5]
+        split: f32,
+    }
+
+    impl Settings for Shadows {
+        const LABEL: ConstLabel<SettingsDomain> = ConstLabel::new("render/shadows");
+    }
+
+    const FILE: &str = r#"
+        [render.shadows]
+        resolution = 4096
+        cascades = { split = 0.75 }
+
+        [other]
+        resolution = 1
+    "#;
+
+    #[test]
+    fn layers() {
+        let sources = SettingsSources::new()
+            .with_toml(FILE, "settings.toml")
+            .unwrap()
+            .with_env([("XAOC_RENDER__SHADOWS__CASCADES__COUNT", "2"), ("HOME", "/root"), ("XAOC_OTHER__X", "1")])
+            .with_args(["app", "--set=render.shadows.filter=pcf", "--set", "render.shadows.resolution=512"])
+            .unwrap();
+        let (shadows, report) = sources.resolve::<Shadows>().unwrap();
+        assert_eq!(
+            shadows,
+            Shadows { resolution: 512, cascades: Cascades { count: 2, split: 0.75 }, filter: Some("pcf".into()) }
+        );
+        assert_eq!(
+            report.fields.iter().map(|(path, layer)| (path.as_str(), *layer)).collect::<Vec<_>>(),
+            [
+                ("resolution", SettingsLayer::CommandLine),
+                ("cascades.count", SettingsLayer::Environment),
+                ("cascades.split", SettingsLayer::File),
+                ("filter", SettingsLayer::CommandLine),
+            ]
+        );
+        assert_eq!(
+            report.to_string(),
+            "[render/shadows]\nresolution = command line\ncascades.count = environment\n\
+             cascades.split = file (settings.toml)\nfilter = command line"
+        );
+
+        let (defaults, report) = SettingsSources::new().resolve::<Shadows>().unwrap();
+        assert_eq!(defaults, Shadows::default());
+        assert!(report.fields.values().all(|layer| *layer == SettingsLayer::Default));
+    }
+
+    #[derive(XaocDefault, Serialize, Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "camelCase")]
+    struct Frames {
+        #[default = 60]
+        max_fps: u32,
+        #[serde(rename = "frame-limit")]
+        frame_limit: Option<u32>,
+        #[serde(rename = "v-sync")]
+        vsync: bool,
+    }
+
+    impl Settings for Frames {
+        const LABEL: ConstLabel<SettingsDomain> = ConstLabel::new("frames");
+    }
+
+    #[test]
+    fn env_names() {
+        let sources = SettingsSources::new().with_env([
+            ("XAOC_SETTINGS", "xaoc.toml"),
+            ("XAOC_FRAMES__MAXFPS", "144"),
+            ("XAOC_FRAMES__V_SYNC", "true"),
+        ]);
+        assert_eq!(sources.env.len(), 2);
+        let (frames, report) = sources.resolve::<Frames>().unwrap();
+        assert_eq!(frames, Frames { max_fps: 144, frame_limit: None, vsync: true });
+        assert_eq!(report.layer("maxFps"), Some(SettingsLayer::Environment));
+        assert_eq!(report.layer("v-sync"), Some(SettingsLayer::Environment));
+
+        // Not in the serialized defaults, as it is `None`, so matched by its name and kept as a string.
+        let sources = SettingsSources::new().with_env([("XAOC_FRAMES__FRAME-LIMIT", "30")]);
+        assert!(matches!(sources.resolve::<Frames>(), Err(SettingsError::Deserialize { .. })));
+        let sources = SettingsSources::new().with_env([("XAOC_FRAMES__FRAME_LIMIT", "30")]);
+        assert_eq!(sources.resolve::<Frames>().unwrap().0.frame_limit, None);
+    }
+
+    #[derive(XaocDefault, Serialize, Deserialize, Debug, PartialEq)]
+    struct Window {
+        #[default("xaoc".into())]
+        title: String,
+        #[default = 1.0]
+        scale: f64,
+    }
+
+    impl Settings for Window {
+        const LABEL: ConstLabel<SettingsDomain> = ConstLabel::new("window");
+    }
+
+    #[test]
+    fn string_values() {
+        for value in ["2024", "true", "1.5", "[1]", "\"quoted\""] {
+            let sources = SettingsSources::new().with_env([("XAOC_WINDOW__TITLE", value)]);
+            assert_eq!(sources.resolve::<Window>().unwrap().0.title, value);
+            let sources = SettingsSources::new().with_args(["--set", &format!("window.title={value}")]).unwrap();
+            assert_eq!(sources.resolve::<Window>().unwrap().0.title, value);
+        }
+        let sources = SettingsSources::new().with_args(["--set", "window.scale=2"]).unwrap();
+        assert_eq!(sources.resolve::<Window>().unwrap().0.scale, 2.0);
+    }
+
+    #[test]
+    fn errors() {
+        let sources = SettingsSources::new().with_env([("XAOC_RENDER__SHADOWS__RESOLUTION", "high")]);
+        assert!(matches!(sources.resolve::<Shadows>(), Err(SettingsError::Deserialize { .. })));
+        assert!(matches!(SettingsSources::new().with_args(["--set", "x"]), Err(SettingsError::InvalidArgument(_))));
+        assert!(matches!(SettingsSources::new().with_args(["--set"]), Err(SettingsError::InvalidArgument(_))));
+        assert!(matches!(SettingsSources::new().with_toml("[", "bad.toml"), Err(SettingsError::Toml { .. })));
+        assert!(matches!(SettingsSources::new().with_file("/nonexistent.toml"), Err(SettingsError::Io { .. })));
+    }
+
+    #[test]
+    fn store() {
+        let mut store =
+            SettingsStore::new(SettingsSources::new().with_args(["--set", "render.shadows.resolution=1"]).unwrap());
+        assert!(store.get::<Shadows>().is_none());
+        assert_eq!(store.register::<Shadows>().unwrap().resolution, 1);
+        assert_eq!(store.register::<Shadows>().unwrap().resolution, 1);
+        assert_eq!(store.reports().len(), 1);
+        assert!(store.to_string().starts_with("[render/shadows]\nresolution = command line"));
+
+        #[derive(Default, Serialize, Deserialize)]
+        struct SameLabel;
+
+        impl Settings for SameLabel {
+            const LABEL: ConstLabel<SettingsDomain> = Shadows::LABEL;
+        }
+
+        let error = store.register::<SameLabel>().map(|_| ()).unwrap_err();
+        assert!(matches!(error, SettingsError::LabelConflict { settings, .. } if settings.ends_with("SameLabel")));
+        assert_eq!(store.reports().len(), 1);
+    }
+}
@@ -0,0 +1,8 @@
+//! The locks of the crate, from `parking_lot` and `once_cell` with the `std` feature, and spin locks otherwise.
+
+#[cfg(feature = "std")]
+pub(crate) use once_cell::sync::Lazy;
+#[cfg(feature = "std")]
+pub(crate) use parking_lot::RwLock;
+#[cfg(not(feature = "std"))]
+pub(crate) use spin::{Lazy, RwLock};
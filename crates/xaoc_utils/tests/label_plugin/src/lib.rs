@@ -0,0 +1,34 @@
+//! A plugin linking its own copy of `xaoc_utils`, loaded as a dynamic library by the `shared_registry` test.
+//! The test links it as well, only to name the same domain type.
+
+use xaoc_utils::label::{install_label_registry, DynLabel, InstallRegistryError, Label, LabelDomain, LabelRegistry};
+
+pub struct PluginDomain;
+impl LabelDomain for PluginDomain {
+    const NAME: &'static str = "xaoc_label_plugin::PluginDomain";
+}
+
+#[no_mangle]
+pub fn xaoc_install_label_registry(registry: LabelRegistry) -> Result<(), InstallRegistryError> {
+    install_label_registry(registry)
+}
+
+#[no_mangle]
+pub fn plugin_label_registry() -> LabelRegistry {
+    LabelRegistry::current()
+}
+
+#[no_mangle]
+pub fn plugin_label(name: &'static str) -> Label<PluginDomain> {
+    Label::new(name)
+}
+
+#[no_mangle]
+pub fn plugin_dyn_label(name: &str) -> DynLabel<PluginDomain> {
+    DynLabel::new(name)
+}
+
+#[no_mangle]
+pub fn plugin_lookup(name: &str) -> Option<Label<PluginDomain>> {
+    Label::lookup(name)
+}
@@ -0,0 +1,59 @@
+use libloading::{Library, Symbol};
+use std::path::PathBuf;
+use std::process::Command;
+use xaoc_label_plugin::PluginDomain;
+use xaoc_utils::label::{DynLabel, DynLabelStats, InstallRegistryError, Label, LabelRegistry};
+
+type Lookup = fn(&str) -> Option<Label<PluginDomain>>;
+
+/// Builds the plugin as a `cdylib` with its own copy of `xaoc_utils`, the way a hot-loaded plugin would be built.
+fn build_plugin() -> PathBuf {
+    let target_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("label_plugin");
+    let mut command = Command::new(env!("CARGO"));
+    command
+        .arg("build")
+        .arg("--manifest-path")
+        .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/label_plugin/Cargo.toml"))
+        .arg("--target-dir")
+        .arg(&target_dir);
+    if cfg!(feature = "wide_label_ids") {
+        command.args(["--features", "xaoc_utils/wide_label_ids"]);
+    }
+    assert!(command.status().unwrap().success(), "failed to build the label plugin");
+    let file = format!("{}xaoc_label_plugin{}", std::env::consts::DLL_PREFIX, std::env::consts::DLL_SUFFIX);
+    target_dir.join("debug").join(file)
+}
+
+#[test]
+fn labels_are_shared_with_plugin() {
+    // Never unloaded, the registry refers to names in the plugin's static memory.
+    let plugin: &'static Library = Box::leak(Box::new(unsafe { Library::new(build_plugin()) }.unwrap()));
+    unsafe {
+        let install: Symbol<fn(LabelRegistry) -> Result<(), InstallRegistryError>> =
+            plugin.get(b"xaoc_install_label_registry").unwrap();
+        let registry: Symbol<fn() -> LabelRegistry> = plugin.get(b"plugin_label_registry").unwrap();
+        let label: Symbol<fn(&'static str) -> Label<PluginDomain>> = plugin.get(b"plugin_label").unwrap();
+        let dyn_label: Symbol<fn(&str) -> DynLabel<PluginDomain>> = plugin.get(b"plugin_dyn_label").unwrap();
+        let lookup: Symbol<Lookup> = plugin.get(b"plugin_lookup").unwrap();
+
+        assert_ne!(registry(), LabelRegistry::current());
+        install(LabelRegistry::current()).unwrap();
+        assert_eq!(registry(), LabelRegistry::current());
+        install(LabelRegistry::current()).unwrap();
+
+        let host = Label::<PluginDomain>::new("host/a");
+        assert_eq!(lookup("host/a"), Some(host));
+
+        let from_plugin = label("plugin/b");
+        assert_eq!(Label::<PluginDomain>::lookup("plugin/b"), Some(from_plugin));
+        assert_eq!(from_plugin.index(), host.index() + 1);
+        assert_eq!(Label::<PluginDomain>::registered(), [host, from_plugin]);
+
+        let reclaimable = dyn_label("plugin/dyn");
+        assert_eq!(DynLabel::<PluginDomain>::lookup("plugin/dyn"), Some(reclaimable.clone()));
+        assert_eq!(DynLabel::<PluginDomain>::stats(), DynLabelStats { labels: 1, bytes: 10, ..Default::default() });
+        drop(reclaimable);
+        assert_eq!(DynLabel::<PluginDomain>::stats().reclaimed, 1);
+        assert_eq!(lookup("plugin/dyn"), None);
+    }
+}